@@ -0,0 +1,193 @@
+use crate::field::{Base, Item, spec_fields};
+use crate::spec::CronSpec;
+use std::collections::BTreeSet;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Warnings for intervals cron can't keep evenly spaced, because it starts
+/// every schedule over at the top of each hour and at midnight.
+///
+/// A schedule whose runs are evenly spaced except for a shorter gap across
+/// midnight gets one warning about that gap; otherwise each minute or hour
+/// step that runs to the end of its field without dividing it, like `*/45`
+/// or `3-59/7`, gets one, as does a step longer than its field, like `*/36`
+/// in the hour field, which only ever matches hour 0.
+///
+/// ```
+/// use cronoisseur::{parse_expression, uneven_intervals};
+///
+/// let spec = parse_expression("*/45 * * * *")?;
+/// assert_eq!(
+///     uneven_intervals(&spec),
+///     ["`*/45` in the minute field starts over every hour, so the gap from :45 to :00 is 15 minutes rather than 45"]
+/// );
+/// assert!(uneven_intervals(&parse_expression("every 90 minutes")?).is_empty());
+/// # Ok::<(), cronoisseur::ParseError>(())
+/// ```
+pub fn uneven_intervals(spec: &CronSpec) -> Vec<String> {
+    if spec.is_reboot() {
+        return Vec::new();
+    }
+    if let Some(warning) = midnight_gap(spec) {
+        return vec![warning];
+    }
+    let mut warnings: Vec<String> = Vec::new();
+    for line in spec.lines() {
+        let Ok([minute, hour, ..]) = spec_fields(&line) else {
+            continue;
+        };
+        for (field, period) in [(&minute, 60u32), (&hour, 24)] {
+            for item in &field.items {
+                // Steps that run to the end of the field: `*/N` and `5-59/N`.
+                let (start, step) = match *item {
+                    Item {
+                        base: Base::Any,
+                        step: Some(step),
+                    } => (0, step),
+                    Item {
+                        base: Base::Range(start, end),
+                        step: Some(step),
+                    } if end == period - 1 && start < step => (start, step),
+                    _ => continue,
+                };
+                if period.is_multiple_of(step) {
+                    continue;
+                }
+                let text = match start {
+                    0 => format!("*/{step}"),
+                    _ => format!("{start}-{}/{step}", period - 1),
+                };
+                let last = start + (period - 1 - start) / step * step;
+                let gap = start + period - last;
+                let warning = if step >= period && period == 60 {
+                    format!(
+                        "`{text}` in the minute field is longer than an hour, and cron starts it over every hour, so it runs once an hour rather than every {step} minutes"
+                    )
+                } else if step >= period {
+                    format!(
+                        "`{text}` in the hour field is longer than a day, and cron starts it over at midnight, so it runs once a day rather than every {step} hours"
+                    )
+                } else if period == 60 {
+                    format!(
+                        "`{text}` in the minute field starts over every hour, so the gap from :{last:02} to :{start:02} is {gap} minutes rather than {step}"
+                    )
+                } else {
+                    format!(
+                        "`{text}` in the hour field starts over at midnight, so the gap from {last:02}:00 to {start:02}:00 is {gap} hours rather than {step}"
+                    )
+                };
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// The warning for runs that are evenly spaced through the day but closer
+/// together across midnight, as every 5 hours or every 100 minutes are.
+fn midnight_gap(spec: &CronSpec) -> Option<String> {
+    let mut times = BTreeSet::new();
+    for line in spec.lines() {
+        let [minute, hour, day_of_month, month, day_of_week] = spec_fields(&line).ok()?;
+        if ![&day_of_month, &month, &day_of_week]
+            .iter()
+            .all(|field| field.is_full())
+        {
+            return None;
+        }
+        let minute = minute.set();
+        times.extend(
+            hour.set()
+                .values()
+                .flat_map(|h| minute.values().map(move |m| h * 60 + m)),
+        );
+    }
+    let times: Vec<u32> = times.into_iter().collect();
+    if times.len() < 3 {
+        return None;
+    }
+    let gap = times[1] - times[0];
+    if times.windows(2).any(|pair| pair[1] - pair[0] != gap) {
+        return None;
+    }
+    let (first, last) = (times[0], times[times.len() - 1]);
+    let wrap = first + MINUTES_PER_DAY - last;
+    let clock = |time: u32| format!("{:02}:{:02}", time / 60, time % 60);
+    (wrap < gap).then(|| {
+        format!(
+            "Runs are {} apart, but cron starts the schedule over every day, so the gap from {} to {} is {}",
+            span(gap),
+            clock(last),
+            clock(first),
+            span(wrap)
+        )
+    })
+}
+
+/// `5 hours`, `1 hour`, `100 minutes`.
+fn span(minutes: u32) -> String {
+    let (amount, unit) = if minutes.is_multiple_of(60) {
+        (minutes / 60, "hour")
+    } else {
+        (minutes, "minute")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn warnings(expression: &str) -> Vec<String> {
+        uneven_intervals(&parse_expression(expression).unwrap())
+    }
+
+    #[test]
+    fn even_runs_with_a_short_night_get_one_warning() {
+        assert_eq!(
+            warnings("0 */5 * * *"),
+            [
+                "Runs are 5 hours apart, but cron starts the schedule over every day, so the gap from 20:00 to 00:00 is 4 hours"
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_that_do_not_divide_their_field_are_named() {
+        assert_eq!(
+            warnings("3-59/7 * * * *"),
+            [
+                "`3-59/7` in the minute field starts over every hour, so the gap from :59 to :03 is 4 minutes rather than 7"
+            ]
+        );
+        assert_eq!(
+            warnings("0 */7 * * 1-5"),
+            [
+                "`*/7` in the hour field starts over at midnight, so the gap from 21:00 to 00:00 is 3 hours rather than 7"
+            ]
+        );
+        assert_eq!(
+            warnings("0 */36 * * *"),
+            [
+                "`*/36` in the hour field is longer than a day, and cron starts it over at midnight, so it runs once a day rather than every 36 hours"
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_that_fit_are_fine() {
+        for expression in [
+            "*/15 * * * *",
+            "5-59/15 * * * *",
+            "0 */6 * * *",
+            "every 90 minutes",
+            "@reboot",
+        ] {
+            assert!(warnings(expression).is_empty(), "{expression}");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::describe::describe_months;
+use crate::field::{Base, Item, spec_fields};
+use crate::spec::CronSpec;
+
+/// The most days `month` (1-12) can have, counting February 29.
+pub(crate) fn month_length(month: u32) -> u32 {
+    match month {
+        2 => 29,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Warnings for days of the month that some of the schedule's months don't
+/// have, so the job skips those months, as the 31st does in April or the
+/// 29th in February outside leap years.
+///
+/// Only lines that name both the days and the months are checked; the 31st
+/// of every month is taken to mean "when there is one".
+///
+/// ```
+/// use cronoisseur::{missing_dates, parse_expression};
+///
+/// let spec = parse_expression("on the 31st of march and april")?;
+/// assert_eq!(
+///     missing_dates(&spec),
+///     ["There is no day 31 in April, so the job skips that month"]
+/// );
+/// assert!(missing_dates(&parse_expression("0 0 31 * *")?).is_empty());
+/// # Ok::<(), cronoisseur::ParseError>(())
+/// ```
+pub fn missing_dates(spec: &CronSpec) -> Vec<String> {
+    if spec.is_reboot() {
+        return Vec::new();
+    }
+    let mut warnings: Vec<String> = Vec::new();
+    for line in spec.lines() {
+        let Ok([_, _, day_of_month, month, day_of_week]) = spec_fields(&line) else {
+            continue;
+        };
+        if month.is_full() || !day_of_week.is_full() {
+            continue;
+        }
+        let months: Vec<u32> = month.set().values().collect();
+        let days = day_of_month.items.iter().filter_map(|item| match *item {
+            Item {
+                base: Base::Value(day),
+                step: None,
+            } => Some(day),
+            _ => None,
+        });
+        for day in days {
+            let missing: Vec<u32> = months
+                .iter()
+                .copied()
+                .filter(|&month| month_length(month) < day)
+                .collect();
+            let warning = if missing.len() == months.len() {
+                format!(
+                    "There is no day {day} in {}, so the job never runs on that day",
+                    describe_months(&missing)
+                )
+            } else if !missing.is_empty() {
+                let skips = if missing.len() == 1 {
+                    "that month"
+                } else {
+                    "those months"
+                };
+                format!(
+                    "There is no day {day} in {}, so the job skips {skips}",
+                    describe_months(&missing)
+                )
+            } else if day == 29 && months.contains(&2) {
+                "February 29 only comes in leap years, so the job skips February in other years"
+                    .to_string()
+            } else {
+                continue;
+            };
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn warnings(expression: &str) -> Vec<String> {
+        missing_dates(&parse_expression(expression).unwrap())
+    }
+
+    #[test]
+    fn months_without_the_day_are_named() {
+        assert_eq!(
+            warnings("on the 30th of january and february"),
+            ["There is no day 30 in February, so the job skips that month"]
+        );
+        assert_eq!(
+            warnings("0 0 31 1-6 *"),
+            ["There is no day 31 in February, April and June, so the job skips those months"]
+        );
+        assert_eq!(
+            missing_dates(&CronSpec::new("0", "0", "31", "4,6", "*", "")),
+            ["There is no day 31 in April and June, so the job never runs on that day"]
+        );
+    }
+
+    #[test]
+    fn february_29_is_a_leap_day() {
+        assert_eq!(
+            warnings("on the 29th of february"),
+            ["February 29 only comes in leap years, so the job skips February in other years"]
+        );
+    }
+
+    #[test]
+    fn any_month_or_a_weekday_means_when_there_is_one() {
+        assert!(warnings("0 0 31 * *").is_empty());
+        assert!(warnings("0 0 31 4 1").is_empty());
+        assert!(warnings("on the 15th of february").is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::dialect::vixie_fields;
+use crate::error::ConvertError;
+use crate::field::spec_fields;
+use crate::spec::{CronEntry, CronSpec};
+use crate::yaml::to_yaml;
+use crate::zone::shift_to_utc;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+
+/// A CI service whose scheduled pipelines take cron syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiPlatform {
+    GithubActions,
+    Gitlab,
+}
+
+impl CiPlatform {
+    fn name(self) -> &'static str {
+        match self {
+            CiPlatform::GithubActions => "GitHub Actions",
+            CiPlatform::Gitlab => "GitLab",
+        }
+    }
+}
+
+/// The schedule block for a CI service plus anything it will not honour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CiSchedule {
+    /// The cron lines the service is given, in the zone it evaluates them in.
+    pub schedules: Vec<CronSpec>,
+    pub snippet: String,
+    pub warnings: Vec<String>,
+}
+
+/// Render the schedule configuration for `platform`.
+///
+/// GitHub Actions evaluates cron in UTC, so a schedule meant in `zone` (by
+/// default its own [`CronSpec::time_zone`]) is shifted to UTC (see [`shift_to_utc`]) using the offset at `at`. GitLab
+/// pipeline schedules carry their own `cron_timezone`, so the schedule is
+/// kept as written and the zone is passed along instead. Schedules that fire
+/// more often than the service starts them produce a warning.
+pub fn render_ci(
+    entry: &CronEntry,
+    platform: CiPlatform,
+    zone: Option<Tz>,
+    at: DateTime<Utc>,
+) -> Result<CiSchedule, ConvertError> {
+    let lines = entry.schedule.lines();
+    let fields = lines
+        .iter()
+        .map(|line| vixie_fields(line, platform.name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let zone = zone.or_else(|| entry.schedule.zone());
+    let mut warnings = Vec::new();
+    let schedules = match (platform, zone) {
+        (CiPlatform::GithubActions, Some(zone)) => {
+            let shift = shift_to_utc(&entry.schedule, zone, at)?;
+            warnings.extend(shift.warnings);
+            shift.schedules
+        }
+        _ => lines
+            .iter()
+            .zip(&fields)
+            .map(|(line, fields)| {
+                let [minute, hour, day_of_month, month, day_of_week] =
+                    fields.each_ref().map(|field| field.canonical());
+                CronSpec::new(
+                    minute,
+                    hour,
+                    day_of_month,
+                    month,
+                    day_of_week,
+                    line.line_explanation().to_string(),
+                )
+            })
+            .collect(),
+    };
+
+    if let Some(gap) = shortest_gap(&lines)?
+        && gap < 5
+    {
+        warnings.push(match platform {
+            CiPlatform::GithubActions => format!(
+                "GitHub Actions runs scheduled workflows at most once every 5 minutes; this schedule fires every {gap} minute(s)"
+            ),
+            CiPlatform::Gitlab => format!(
+                "GitLab.com starts scheduled pipelines every 5 minutes (every 10 on self-managed instances by default); this schedule fires every {gap} minute(s)"
+            ),
+        });
+    }
+
+    let snippet = match platform {
+        CiPlatform::GithubActions => github_snippet(entry, &schedules),
+        CiPlatform::Gitlab => gitlab_snippet(entry, &schedules, zone),
+    };
+    Ok(CiSchedule {
+        schedules,
+        snippet,
+        warnings,
+    })
+}
+
+fn github_snippet(entry: &CronEntry, schedules: &[CronSpec]) -> String {
+    let mut lines = vec!["on:".to_string(), "  schedule:".to_string()];
+    lines.push(format!(
+        "    # {}",
+        entry
+            .comment
+            .as_deref()
+            .unwrap_or(&entry.schedule.explanation)
+    ));
+    for spec in schedules {
+        lines.push(format!(
+            "    - cron: {}  # {}",
+            Value::String(spec.as_string()),
+            spec.line_explanation()
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// A job that only runs for scheduled pipelines, headed by the settings for
+/// the pipeline schedules themselves, which live outside `.gitlab-ci.yml`.
+/// Each cron line needs a schedule of its own.
+fn gitlab_snippet(entry: &CronEntry, schedules: &[CronSpec], zone: Option<Tz>) -> String {
+    let description = entry
+        .comment
+        .as_deref()
+        .unwrap_or(&entry.schedule.explanation);
+    let mut header = vec![if schedules.len() == 1 {
+        "# Pipeline schedule (Build > Pipeline schedules, or the pipeline_schedules API):"
+            .to_string()
+    } else {
+        format!(
+            "# {} pipeline schedules (Build > Pipeline schedules, or the pipeline_schedules API):",
+            schedules.len()
+        )
+    }];
+    for spec in schedules {
+        header.extend([
+            format!(
+                "#   description: {}",
+                Value::String(description.to_string())
+            ),
+            format!("#   cron: {}", Value::String(spec.as_string())),
+            format!(
+                "#   cron_timezone: {}",
+                Value::String(zone.map_or("UTC", |zone| zone.name()).to_string())
+            ),
+        ]);
+    }
+
+    let mut job = Map::new();
+    if !entry.env.is_empty() {
+        job.insert(
+            "variables".to_string(),
+            Value::Object(
+                entry
+                    .env
+                    .iter()
+                    .map(|env| (env.key.clone(), Value::String(env.value.clone())))
+                    .collect(),
+            ),
+        );
+    }
+    job.insert(
+        "rules".to_string(),
+        json!([{ "if": "$CI_PIPELINE_SOURCE == \"schedule\"" }]),
+    );
+    job.insert("script".to_string(), json!([entry.command]));
+    let mut root = Map::new();
+    root.insert(entry.managed_id(), Value::Object(job));
+
+    format!("{}\n{}", header.join("\n"), to_yaml(&root))
+}
+
+/// Minutes between the two closest runs within a day across `lines`, if it
+/// fires more than once.
+fn shortest_gap(lines: &[CronSpec]) -> Result<Option<u32>, ConvertError> {
+    let mut times = BTreeSet::new();
+    for line in lines {
+        let [minute, hour, ..] = spec_fields(line)?;
+        let minute = minute.set();
+        times.extend(
+            hour.set()
+                .values()
+                .flat_map(|h| minute.values().map(move |m| h * 60 + m)),
+        );
+    }
+    let times: Vec<u32> = times.into_iter().collect();
+    let (Some(first), Some(last)) = (times.first(), times.last()) else {
+        return Ok(None);
+    };
+    if times.len() < 2 {
+        return Ok(None);
+    }
+    let wrap = first + 24 * 60 - last;
+    Ok(times
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .chain([wrap])
+        .min())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn entry(expression: &str) -> CronEntry {
+        CronEntry {
+            id: None,
+            schedule: parse_expression(expression).unwrap(),
+            command: "./report.sh".to_string(),
+            comment: None,
+            env: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn each_github_cron_line_is_described_on_its_own() {
+        let entry = entry("daily at 09:00 and 17:30");
+        let ci = render_ci(&entry, CiPlatform::GithubActions, None, Utc::now()).unwrap();
+        assert_eq!(
+            ci.snippet,
+            format!(
+                "on:\n  schedule:\n    # {}\n    - cron: \"0 9 * * *\"  # At 09:00\n    - cron: \"30 17 * * *\"  # At 17:30\n",
+                entry.schedule.explanation
+            )
+        );
+    }
+}
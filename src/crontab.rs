@@ -0,0 +1,713 @@
+use crate::error::{Error, ParseError, Result};
+use crate::guard::strip_day_guard;
+use crate::parse::parse_expression;
+use crate::render::render_block;
+use crate::spec::{CronEntry, CronSpec, EnvVar};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::ops::Range;
+
+/// What writing an entry did to the target crontab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteStatus {
+    /// The entry was not present and has been added.
+    New,
+    /// An identical entry was already present; nothing changed.
+    Unchanged,
+    /// A block was present under the entry's explicit id, or with the same
+    /// schedule and command, but differed, and has been rewritten.
+    Replaced,
+}
+
+/// Rough classification of a single crontab line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Line<'a> {
+    Blank,
+    Comment,
+    Env,
+    /// A five-field job: the schedule fields and the command that follows.
+    Job {
+        fields: [&'a str; 5],
+        command: &'a str,
+    },
+    /// A job scheduled with an `@name` macro such as `@daily` or `@reboot`.
+    Macro {
+        name: &'a str,
+        command: &'a str,
+    },
+    Other,
+}
+
+impl<'a> Line<'a> {
+    pub(crate) fn classify(line: &'a str) -> Self {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return Line::Blank;
+        }
+        if trimmed.starts_with('#') {
+            return Line::Comment;
+        }
+        if is_env_assignment(trimmed) {
+            return Line::Env;
+        }
+        if trimmed.starts_with('@') {
+            let (name, command) = trimmed
+                .split_once(char::is_whitespace)
+                .unwrap_or((trimmed, ""));
+            return Line::Macro {
+                name,
+                command: command.trim(),
+            };
+        }
+        match split_fields(trimmed) {
+            Some((fields, command)) => Line::Job { fields, command },
+            None => Line::Other,
+        }
+    }
+}
+
+fn is_env_assignment(line: &str) -> bool {
+    let Some((key, _)) = line.split_once('=') else {
+        return false;
+    };
+    let key = key.trim_end();
+    !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split a job line into its five schedule fields and the command.
+fn split_fields(line: &str) -> Option<([&str; 5], &str)> {
+    let mut fields = [""; 5];
+    let mut rest = line;
+    for field in &mut fields {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace)?;
+        *field = &trimmed[..end];
+        rest = &trimmed[end..];
+    }
+    let command = rest.trim();
+    (!command.is_empty()).then_some((fields, command))
+}
+
+/// Prefix of the comment line that opens every block written by
+/// [`render_entry`](crate::render_entry).
+pub(crate) const ID_MARKER: &str = "# cronoisseur:id=";
+
+/// The variable cronie evaluates the schedules below it in.
+pub(crate) const CRON_TZ: &str = "CRON_TZ";
+
+/// Variables that set the zone jobs run in, which a block that sets them
+/// puts back after its jobs.
+pub(crate) const ZONE_VARIABLES: [&str; 2] = [CRON_TZ, "TZ"];
+
+/// Merge `entry` into the text of an existing crontab.
+///
+/// A block with the entry's `# cronoisseur:id=` marker is rewritten in place
+/// when the entry has an explicit id, or when the block runs the same
+/// schedule and command; a derived id that marks some other job is an error,
+/// so one job never silently replaces another. Blocks written before markers
+/// existed are found by an unmarked job line with the same schedule and
+/// command. A block that is already identical is left alone, and anything
+/// else is appended. With `allow_duplicate` the entry is always appended,
+/// but never under a marker the crontab already has: a derived id that is
+/// taken moves on to the first free one from [`unused_id`], while an
+/// explicit one is an error.
+pub fn merge_entry(
+    existing: &str,
+    entry: &CronEntry,
+    allow_duplicate: bool,
+) -> Result<(String, WriteStatus)> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let mut id = entry.managed_id();
+    let found = match managed_block(&lines, &id) {
+        Some(_) if allow_duplicate && entry.id.is_none() => {
+            id = unused_id(existing, &id);
+            None
+        }
+        Some(range) => {
+            let same_job = entry.id.is_some()
+                || find_entry(existing, &id).is_some_and(|found| same_job(&found, entry));
+            if allow_duplicate || !same_job {
+                return Err(Error::DuplicateId { id });
+            }
+            Some(range)
+        }
+        None if allow_duplicate => None,
+        None => unmanaged_block(&lines, entry),
+    };
+    let entry = &CronEntry {
+        id: Some(id),
+        ..entry.clone()
+    };
+    if let Some(range) = found {
+        let block = render_block(entry, &env_before(&lines, range.start));
+        if lines[range.clone()].join("\n") == block {
+            return Ok((existing.to_string(), WriteStatus::Unchanged));
+        }
+        return Ok((splice(&lines, range, &block), WriteStatus::Replaced));
+    }
+
+    let block = render_block(entry, &env_before(&lines, lines.len()));
+    let mut contents = existing.to_string();
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&block);
+    contents.push('\n');
+    Ok((contents, WriteStatus::New))
+}
+
+/// The first of `id`, `id-2`, `id-3` and so on that no `# cronoisseur:id=`
+/// marker in `existing` uses.
+///
+/// ```
+/// use cronoisseur::unused_id;
+///
+/// let existing = "# cronoisseur:id=backup\n0 2 * * * backup.sh\n";
+/// assert_eq!(unused_id(existing, "backup"), "backup-2");
+/// assert_eq!(unused_id(existing, "report"), "report");
+/// ```
+pub fn unused_id(existing: &str, id: &str) -> String {
+    let lines: Vec<&str> = existing.lines().collect();
+    std::iter::once(id.to_string())
+        .chain((2..).map(|n| format!("{id}-{n}")))
+        .find(|candidate| managed_block(&lines, candidate).is_none())
+        .expect("only finitely many ids are taken")
+}
+
+/// Whether two entries run the same command on the same schedule.
+fn same_job(a: &CronEntry, b: &CronEntry) -> bool {
+    let lines = |entry: &CronEntry| -> Vec<String> {
+        entry
+            .schedule
+            .lines()
+            .iter()
+            .map(CronSpec::as_string)
+            .collect()
+    };
+    a.command.trim() == b.command.trim()
+        && a.schedule.zone() == b.schedule.zone()
+        && lines(a) == lines(b)
+}
+
+/// The entry stored under `id`, if the crontab text has one.
+pub fn find_entry(existing: &str, id: &str) -> Option<CronEntry> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let range = managed_block(&lines, id)?;
+    let mut comment = None;
+    let mut env = Vec::new();
+    let mut schedules = Vec::new();
+    let mut command = None;
+    for line in &lines[range.start + 1..range.end] {
+        match Line::classify(line) {
+            Line::Comment if comment.is_none() => {
+                comment = Some(line.trim().trim_start_matches('#').trim().to_string());
+            }
+            // Assignments after the jobs put the zone back for the lines below.
+            Line::Env if command.is_some() => {}
+            Line::Env => {
+                let (key, value) = line.split_once('=')?;
+                env.push(EnvVar {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                });
+            }
+            Line::Job { .. } | Line::Macro { .. } => {
+                let (schedule, rest) = job_schedule(line)?;
+                let (schedule, rest) = unguard(schedule.ok()?, rest, &env);
+                schedules.push(schedule);
+                command = Some(rest);
+            }
+            _ => {}
+        }
+    }
+    let explanation = schedules
+        .iter()
+        .map(|schedule| schedule.explanation.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let schedule = match schedules.len() {
+        0 => return None,
+        1 => schedules.remove(0),
+        _ => CronSpec::from_lines(schedules, explanation),
+    };
+    let (schedule, env) = apply_cron_tz(schedule, &env);
+    Some(CronEntry {
+        id: Some(id.to_string()),
+        schedule,
+        command: command?,
+        comment,
+        env,
+    })
+}
+
+/// Rewrite the block carrying `entry`'s id. Returns `None` when there is none.
+pub fn replace_entry(existing: &str, entry: &CronEntry) -> Option<String> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let range = managed_block(&lines, &entry.managed_id())?;
+    let block = render_block(entry, &env_before(&lines, range.start));
+    Some(splice(&lines, range, &block))
+}
+
+/// Drop the block carrying `id`. Returns `None` when there is none.
+pub fn remove_entry(existing: &str, id: &str) -> Option<String> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let range = managed_block(&lines, id)?;
+    Some(splice(&lines, range, ""))
+}
+
+/// Replace `range` of `lines` with the lines of `block`, leaving every other
+/// line exactly as it was.
+fn splice(lines: &[&str], range: Range<usize>, block: &str) -> String {
+    let mut merged = lines[..range.start].to_vec();
+    merged.extend(block.lines());
+    merged.extend(&lines[range.end..]);
+    let mut contents = merged.join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Line range from the `id` marker through the first job line after it, any
+/// job lines right below that run the same command, as a schedule that
+/// takes several lines is written, and the lines after them putting back
+/// the zone variables the block set.
+fn managed_block(lines: &[&str], id: &str) -> Option<Range<usize>> {
+    let start = lines.iter().position(|line| marker_id(line) == Some(id))?;
+    let mut end = start + 1;
+    let mut zoned: Vec<String> = Vec::new();
+    while let Some(line) = lines.get(end) {
+        match Line::classify(line) {
+            Line::Comment if marker_id(line).is_none() => end += 1,
+            Line::Env => {
+                if let Some(var) = parse_assignment(line)
+                    && ZONE_VARIABLES.contains(&var.key.as_str())
+                {
+                    zoned.push(var.key);
+                }
+                end += 1;
+            }
+            Line::Job { .. } | Line::Macro { .. } => {
+                let command = job_schedule(line).map(|(_, command)| command);
+                end += 1;
+                while lines
+                    .get(end)
+                    .is_some_and(|next| job_schedule(next).map(|(_, command)| command) == command)
+                {
+                    end += 1;
+                }
+                while let Some(var) = lines
+                    .get(end)
+                    .filter(|next| Line::classify(next) == Line::Env)
+                    .and_then(|next| parse_assignment(next))
+                {
+                    let Some(index) = zoned.iter().position(|key| *key == var.key) else {
+                        break;
+                    };
+                    zoned.remove(index);
+                    end += 1;
+                }
+                return Some(start..end);
+            }
+            _ => break,
+        }
+    }
+    Some(start..end)
+}
+
+/// The assignments in force at line `index`: the last value of each
+/// variable set above it.
+fn env_before(lines: &[&str], index: usize) -> Vec<EnvVar> {
+    let mut env: Vec<EnvVar> = Vec::new();
+    for line in &lines[..index] {
+        if Line::classify(line) == Line::Env
+            && let Some(var) = parse_assignment(line)
+        {
+            env.retain(|existing| existing.key != var.key);
+            env.push(var);
+        }
+    }
+    env
+}
+
+fn marker_id(line: &str) -> Option<&str> {
+    line.trim().strip_prefix(ID_MARKER).map(str::trim)
+}
+
+/// Line range of an unmarked block whose job line runs `entry`'s schedule and
+/// command, including the environment lines directly above it and, above
+/// those, a single comment line when the entry carries one (the shape
+/// [`render_entry`](crate::render_entry) produced before markers). Longer comment runs, such as a
+/// crontab's header, stay put.
+fn unmanaged_block(lines: &[&str], entry: &CronEntry) -> Option<Range<usize>> {
+    let schedule = entry.schedule.as_string();
+    let wanted: Vec<&str> = schedule.split_whitespace().collect();
+    lines.iter().enumerate().find_map(|(job, line)| {
+        let (fields, command) = match Line::classify(line) {
+            Line::Job { fields, command } => (fields.to_vec(), command),
+            Line::Macro { name, command } => (vec![name], command),
+            _ => return None,
+        };
+        if fields != wanted || command != entry.command.trim() {
+            return None;
+        }
+        let mut start = job;
+        while start > 0 && Line::classify(lines[start - 1]) == Line::Env {
+            start -= 1;
+        }
+        if entry.comment.is_some()
+            && start > 0
+            && Line::classify(lines[start - 1]) == Line::Comment
+            && marker_id(lines[start - 1]).is_none()
+        {
+            start -= 1;
+        }
+        let managed = start > 0 && marker_id(lines[start - 1]).is_some();
+        (!managed).then_some(start..job + 1)
+    })
+}
+
+/// A scheduled job found in an existing crontab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrontabJob {
+    /// 1-based line number of the job line.
+    pub line: usize,
+    /// The user column of a system crontab (`/etc/crontab`, `/etc/cron.d/*`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// The job with the comment directly above it and every environment
+    /// assignment in scope at that line.
+    pub entry: CronEntry,
+}
+
+/// A non-blank line that is neither a comment, an assignment nor a valid job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnparsedLine {
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// Everything [`parse_crontab`] found in a crontab.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Crontab {
+    pub jobs: Vec<CrontabJob>,
+    pub unparsed: Vec<UnparsedLine>,
+}
+
+/// Parse the text of a crontab.
+///
+/// `system` selects the `/etc/crontab` format, where a user column sits
+/// between the schedule and the command. Jobs scheduled with a macro such as
+/// `@daily` or `@reboot` keep it (see [`CronSpec`](crate::CronSpec)), and a
+/// `CRON_TZ` set above a job becomes its schedule's time zone.
+pub fn parse_crontab(contents: &str, system: bool) -> Crontab {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut crontab = Crontab::default();
+    let mut env: Vec<EnvVar> = Vec::new();
+
+    for (index, raw) in lines.iter().enumerate() {
+        let line = index + 1;
+        let unparsed = |reason: String| UnparsedLine {
+            line,
+            text: raw.to_string(),
+            reason,
+        };
+        let (schedule, rest) = match Line::classify(raw) {
+            Line::Blank | Line::Comment => continue,
+            Line::Env => {
+                if let Some(var) = parse_assignment(raw) {
+                    env.retain(|existing| existing.key != var.key);
+                    env.push(var);
+                }
+                continue;
+            }
+            Line::Other => {
+                crontab.unparsed.push(unparsed(
+                    "Expected five schedule fields and a command".to_string(),
+                ));
+                continue;
+            }
+            Line::Job { .. } | Line::Macro { .. } => {
+                job_schedule(raw).expect("job and macro lines have a schedule")
+            }
+        };
+
+        let schedule = match schedule {
+            Ok(schedule) => schedule,
+            Err(err) => {
+                crontab.unparsed.push(unparsed(err.to_string()));
+                continue;
+            }
+        };
+        let Some((user, command)) = split_user(rest, system) else {
+            crontab
+                .unparsed
+                .push(unparsed("Missing command".to_string()));
+            continue;
+        };
+        let (id, comment) = block_header(&lines, index);
+        let (schedule, env) = apply_cron_tz(schedule, &env);
+        let (schedule, command) = unguard(schedule, command, &env);
+        crontab.jobs.push(CrontabJob {
+            line,
+            user,
+            entry: CronEntry {
+                id,
+                schedule,
+                command,
+                comment,
+                env,
+            },
+        });
+    }
+    crontab
+}
+
+/// Move a valid `CRON_TZ` out of the environment and into the schedule.
+fn apply_cron_tz(schedule: CronSpec, env: &[EnvVar]) -> (CronSpec, Vec<EnvVar>) {
+    let zone = env
+        .iter()
+        .find(|var| var.key == CRON_TZ)
+        .and_then(|var| var.value.parse::<Tz>().ok());
+    let Some(zone) = zone.filter(|_| schedule.time_zone.is_none() && !schedule.is_reboot()) else {
+        return (schedule, env.to_vec());
+    };
+    let env = env
+        .iter()
+        .filter(|var| var.key != CRON_TZ)
+        .cloned()
+        .collect();
+    (schedule.with_time_zone(zone), env)
+}
+
+/// Fold a [`day_guard`](crate::day_guard) check at the start of `command`
+/// back into the schedule it was written for.
+fn unguard(schedule: CronSpec, command: &str, env: &[EnvVar]) -> (CronSpec, String) {
+    let cron_tz = env
+        .iter()
+        .find(|var| var.key == CRON_TZ)
+        .map(|var| var.value.as_str());
+    match strip_day_guard(&schedule, command, cron_tz) {
+        Some(unguarded) => unguarded,
+        None => (schedule, command.to_string()),
+    }
+}
+
+/// The parsed schedule of a job or macro line and the rest of the line.
+fn job_schedule(line: &str) -> Option<(Result<CronSpec, ParseError>, &str)> {
+    match Line::classify(line) {
+        Line::Job { fields, command } => Some((parse_expression(&fields.join(" ")), command)),
+        Line::Macro { name, command } => Some((parse_expression(name), command)),
+        _ => None,
+    }
+}
+
+/// Split off the user column of a system crontab.
+fn split_user(rest: &str, system: bool) -> Option<(Option<String>, &str)> {
+    if !system {
+        return (!rest.is_empty()).then_some((None, rest));
+    }
+    let (user, command) = rest.split_once(char::is_whitespace)?;
+    let command = command.trim();
+    (!command.is_empty()).then_some((Some(user.to_string()), command))
+}
+
+/// The managed id and comment of the block ending at the job on line `job`:
+/// the comment directly above it (above any environment lines), and the
+/// `# cronoisseur:id=` marker above that, if any.
+fn block_header(lines: &[&str], job: usize) -> (Option<String>, Option<String>) {
+    let mut start = job;
+    while start > 0 && Line::classify(lines[start - 1]) == Line::Env {
+        start -= 1;
+    }
+    let mut comment = None;
+    if start > 0
+        && Line::classify(lines[start - 1]) == Line::Comment
+        && marker_id(lines[start - 1]).is_none()
+    {
+        comment = Some(
+            lines[start - 1]
+                .trim()
+                .trim_start_matches('#')
+                .trim()
+                .to_string(),
+        );
+        start -= 1;
+    }
+    let id = start
+        .checked_sub(1)
+        .and_then(|above| marker_id(lines[above]))
+        .map(str::to_string);
+    (id, comment)
+}
+
+fn parse_assignment(line: &str) -> Option<EnvVar> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.trim();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(value);
+    Some(EnvVar {
+        key: key.trim().to_string(),
+        value: unquoted.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zone::host_zone;
+
+    fn entry(expression: &str, command: &str) -> CronEntry {
+        CronEntry {
+            id: None,
+            schedule: parse_expression(expression).unwrap(),
+            command: command.to_string(),
+            comment: None,
+            env: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn zoned_block_puts_cron_tz_back() {
+        let existing = "CRON_TZ=America/New_York\n0 1 * * * early.sh\n";
+        let zoned = entry("daily at 05:00 Europe/Berlin", "a.sh");
+        let (merged, status) = merge_entry(existing, &zoned, false).unwrap();
+        assert_eq!(status, WriteStatus::New);
+        assert_eq!(
+            merged,
+            "CRON_TZ=America/New_York\n0 1 * * * early.sh\n# cronoisseur:id=a-sh\nCRON_TZ=Europe/Berlin\n0 5 * * * a.sh\nCRON_TZ=America/New_York\n"
+        );
+
+        let (merged, _) = merge_entry(&merged, &entry("daily at 05:00", "b.sh"), false).unwrap();
+        let jobs = parse_crontab(&merged, false).jobs;
+        assert_eq!(
+            jobs[2].entry.schedule.zone(),
+            Some(chrono_tz::America::New_York)
+        );
+    }
+
+    #[test]
+    fn zoned_block_without_a_previous_zone_restores_the_host() {
+        let zoned = entry("daily at 05:00 Europe/Berlin", "a.sh");
+        let (merged, _) = merge_entry("", &zoned, false).unwrap();
+        let jobs = parse_crontab(&format!("{merged}0 5 * * * b.sh\n"), false).jobs;
+        assert_eq!(
+            jobs[1].entry.schedule.zone().unwrap_or(host_zone()),
+            host_zone()
+        );
+    }
+
+    #[test]
+    fn restore_line_belongs_to_its_block() {
+        let zoned = entry("daily at 05:00 Europe/Berlin", "a.sh");
+        let (merged, _) = merge_entry("CRON_TZ=Asia/Tokyo\n", &zoned, false).unwrap();
+        let (merged, _) = merge_entry(&merged, &entry("daily at 05:00", "b.sh"), false).unwrap();
+
+        let found = find_entry(&merged, "a-sh").unwrap();
+        assert!(found.env.iter().all(|env| env.value != "Asia/Tokyo"));
+        assert_eq!(
+            merge_entry(&merged, &zoned, false).unwrap().1,
+            WriteStatus::Unchanged
+        );
+
+        let moved = CronEntry {
+            id: Some("a-sh".to_string()),
+            ..entry("daily at 06:00 Europe/Berlin", "a.sh")
+        };
+        let replaced = replace_entry(&merged, &moved).unwrap();
+        assert_eq!(replaced.matches("CRON_TZ=Asia/Tokyo").count(), 2);
+        assert_eq!(
+            remove_entry(&merged, "a-sh").unwrap(),
+            "CRON_TZ=Asia/Tokyo\n# cronoisseur:id=b-sh\n0 5 * * * b.sh\n"
+        );
+    }
+
+    #[test]
+    fn writing_the_same_job_twice_changes_nothing() {
+        let job = entry("daily at 02:00", "backup.sh");
+        let (merged, status) = merge_entry("MAILTO=ops\n", &job, false).unwrap();
+        assert_eq!(status, WriteStatus::New);
+        assert_eq!(
+            merged,
+            "MAILTO=ops\n# cronoisseur:id=backup-sh\n0 2 * * * backup.sh\n"
+        );
+        assert_eq!(
+            merge_entry(&merged, &job, false).unwrap(),
+            (merged, WriteStatus::Unchanged)
+        );
+    }
+
+    #[test]
+    fn an_unmarked_copy_of_the_job_is_taken_over() {
+        let existing = "0 1 * * * other.sh\n0 2 * * * backup.sh\n";
+        let (merged, status) =
+            merge_entry(existing, &entry("daily at 02:00", "backup.sh"), false).unwrap();
+        assert_eq!(status, WriteStatus::Replaced);
+        assert_eq!(
+            merged,
+            "0 1 * * * other.sh\n# cronoisseur:id=backup-sh\n0 2 * * * backup.sh\n"
+        );
+    }
+
+    #[test]
+    fn a_derived_id_never_replaces_another_job() {
+        let existing = "# cronoisseur:id=backup-sh\n0 3 * * * backup.sh\n";
+        let err = merge_entry(existing, &entry("daily at 02:00", "backup.sh"), false).unwrap_err();
+        assert!(matches!(err, Error::DuplicateId { id } if id == "backup-sh"));
+
+        let explicit = CronEntry {
+            id: Some("backup-sh".to_string()),
+            ..entry("daily at 02:00", "backup.sh")
+        };
+        assert_eq!(
+            merge_entry(existing, &explicit, false).unwrap(),
+            (
+                "# cronoisseur:id=backup-sh\n0 2 * * * backup.sh\n".to_string(),
+                WriteStatus::Replaced
+            )
+        );
+    }
+
+    #[test]
+    fn crontabs_are_read_with_their_scope() {
+        let contents = "SHELL=/bin/bash\n# nightly\n@daily root cleanup.sh\n17 * * * * root   cd / && run-parts\nnot a job\n";
+        let crontab = parse_crontab(contents, true);
+        assert_eq!(crontab.jobs.len(), 2);
+        let first = &crontab.jobs[0];
+        assert_eq!(first.line, 3);
+        assert_eq!(first.user.as_deref(), Some("root"));
+        assert_eq!(first.entry.schedule.as_string(), "@daily");
+        assert_eq!(first.entry.comment.as_deref(), Some("nightly"));
+        assert_eq!(first.entry.env[0].key, "SHELL");
+        assert_eq!(crontab.jobs[1].entry.command, "cd / && run-parts");
+        assert_eq!(crontab.unparsed.len(), 1);
+        assert_eq!(crontab.unparsed[0].line, 5);
+    }
+
+    #[test]
+    fn duplicates_go_under_the_next_free_id() {
+        let job = entry("daily at 02:00", "backup.sh");
+        let (once, _) = merge_entry("", &job, true).unwrap();
+        let (twice, status) = merge_entry(&once, &job, true).unwrap();
+        assert_eq!(status, WriteStatus::New);
+        let (thrice, _) = merge_entry(&twice, &job, true).unwrap();
+        assert_eq!(
+            thrice,
+            "# cronoisseur:id=backup-sh\n0 2 * * * backup.sh\n# cronoisseur:id=backup-sh-2\n0 2 * * * backup.sh\n# cronoisseur:id=backup-sh-3\n0 2 * * * backup.sh\n"
+        );
+        assert_eq!(unused_id(&thrice, "backup-sh"), "backup-sh-4");
+
+        let explicit = CronEntry {
+            id: Some("backup-sh-2".to_string()),
+            ..job
+        };
+        let err = merge_entry(&thrice, &explicit, true).unwrap_err();
+        assert!(matches!(err, Error::DuplicateId { id } if id == "backup-sh-2"));
+    }
+}
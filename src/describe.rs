@@ -0,0 +1,460 @@
+use crate::error::ParseError;
+use crate::field::{Base, Field, FieldKind, Item, parse_fields, spec_extras, spec_fields};
+use crate::parse::capitalize;
+use crate::spec::CronSpec;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const ORDINALS: [&str; 5] = ["first", "second", "third", "fourth", "fifth"];
+
+const DAY_NAMES: [&str; 8] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Describe a five-field cron expression in English.
+///
+/// ```
+/// assert_eq!(cronoisseur::explain("30 3 * * 1").unwrap(), "At 03:30 on Monday");
+/// ```
+pub fn explain(expression: &str) -> Result<String, ParseError> {
+    let parts: Vec<&str> = expression.split_whitespace().collect();
+    let found = parts.len();
+    let parts: [&str; 5] = parts.try_into().map_err(|_| ParseError::WrongFieldCount {
+        span: span_of_words(expression),
+        expected: "5",
+        found,
+    })?;
+    Ok(describe_fields(&parse_fields(expression, parts, false)?))
+}
+
+pub(crate) fn span_of_words(expression: &str) -> std::ops::Range<usize> {
+    let start = expression.len() - expression.trim_start().len();
+    start..expression.trim_end().len().max(start)
+}
+
+/// One cron line of a schedule described on its own, seconds and year
+/// included; `None` for `@reboot`, which has no fields.
+pub(crate) fn describe_line(spec: &CronSpec) -> Option<String> {
+    let fields = spec_fields(spec).ok()?;
+    let (second, year) = spec_extras(spec).ok()?;
+    Some(describe_schedule(second.as_ref(), &fields, year.as_ref()))
+}
+
+pub(crate) fn describe_fields(fields: &[Field; 5]) -> String {
+    describe_schedule(None, fields, None)
+}
+
+/// Describe a schedule that may also have a seconds and a year field.
+pub(crate) fn describe_schedule(
+    second: Option<&Field>,
+    fields: &[Field; 5],
+    year: Option<&Field>,
+) -> String {
+    let [minute, hour, day_of_month, month, day_of_week] = fields;
+    let second = second.filter(|second| plain_values(second) != Some(vec![0]));
+    let mut sentence = match second {
+        Some(second) => describe_time_with_seconds(second, minute, hour),
+        None => describe_time(minute, hour),
+    };
+
+    let dom = describe_day_of_month(day_of_month);
+    let dow = describe_day_of_week(day_of_week);
+    match (dom, dow) {
+        (Some(dom), Some(dow)) if !day_of_month.is_star() && !day_of_week.is_star() => {
+            sentence.push_str(&format!(" {dom} or {dow}"));
+        }
+        (Some(dom), Some(dow)) => {
+            let dow = dow.strip_prefix("on ").unwrap_or(&dow);
+            sentence.push_str(&format!(" {dom}, only on {dow}"));
+        }
+        (dom, dow) => {
+            for part in [dom, dow].into_iter().flatten() {
+                sentence.push(' ');
+                sentence.push_str(&part);
+            }
+        }
+    }
+    if let Some(months) = describe_month(month) {
+        sentence.push(' ');
+        sentence.push_str(&months);
+    }
+    if let Some(years) = year.and_then(describe_year) {
+        sentence.push(' ');
+        sentence.push_str(&years);
+    }
+    sentence
+}
+
+/// `At 07:15:30`, or the seconds ahead of the usual minute and hour wording.
+fn describe_time_with_seconds(second: &Field, minute: &Field, hour: &Field) -> String {
+    if let (Some(seconds), Some(minutes), Some(hours)) = (
+        plain_values(second),
+        plain_values(minute),
+        plain_values(hour),
+    ) && seconds.len() * minutes.len() * hours.len() <= 8
+    {
+        let mut times = Vec::new();
+        for h in &hours {
+            for m in &minutes {
+                times.extend(seconds.iter().map(|s| format!("{h:02}:{m:02}:{s:02}")));
+            }
+        }
+        return format!("At {}", join_and(&times));
+    }
+    let seconds = match plain_values(second) {
+        Some(values) if values.len() == 1 => format!("At second {}", values[0]),
+        Some(values) => format!("At seconds {}", join_numbers(&values)),
+        None => capitalize(&describe_items(second, "second", |v| format!("second {v}"))),
+    };
+    let rest = describe_time(minute, hour);
+    let rest = match rest.strip_prefix("Every minute") {
+        Some(tail) => format!("every minute{tail}"),
+        None => match rest.strip_prefix("At") {
+            Some(tail) => format!("at{tail}"),
+            None => rest,
+        },
+    };
+    format!("{seconds}, {rest}")
+}
+
+fn describe_time(minute: &Field, hour: &Field) -> String {
+    if let (Some(minutes), Some(hours)) = (plain_values(minute), plain_values(hour))
+        && minutes.len() * hours.len() <= 8
+    {
+        let times = hours
+            .iter()
+            .flat_map(|h| minutes.iter().map(move |m| format!("{h:02}:{m:02}")))
+            .collect::<Vec<_>>();
+        return format!("At {}", join_and(&times));
+    }
+
+    let minute_part = match minute.items.as_slice() {
+        [
+            Item {
+                base: Base::Any,
+                step: None,
+            },
+        ] => "Every minute".to_string(),
+        _ => match plain_values(minute) {
+            Some(values) if values.len() == 1 => format!("At minute {}", values[0]),
+            Some(values) => format!("At minutes {}", join_numbers(&values)),
+            None => capitalize(&describe_items(minute, "minute", |v| format!(":{v:02}"))),
+        },
+    };
+
+    let hour_part = match hour.items.as_slice() {
+        [
+            Item {
+                base: Base::Any,
+                step: None,
+            },
+        ] => None,
+        _ => Some(match plain_values(hour) {
+            Some(values) => {
+                let labels = values
+                    .iter()
+                    .map(|h| format!("{h:02}:00"))
+                    .collect::<Vec<_>>();
+                let noun = if values.len() == 1 { "hour" } else { "hours" };
+                format!("during the {} {noun}", join_and(&labels))
+            }
+            None => match hour.items.as_slice() {
+                [
+                    Item {
+                        base: Base::Range(start, end),
+                        step: None,
+                    },
+                ] => format!("between {start:02}:00 and {end:02}:59"),
+                _ => describe_items(hour, "hour", |v| format!("{v:02}:00")),
+            },
+        }),
+    };
+
+    match hour_part {
+        Some(hours) => format!("{minute_part}, {hours}"),
+        None => minute_part,
+    }
+}
+
+fn describe_day_of_month(field: &Field) -> Option<String> {
+    if is_unrestricted(field) {
+        return None;
+    }
+    if field.has_special() {
+        return Some(describe_special_days(field, |v| format!("day {v}")));
+    }
+    Some(match plain_values(field) {
+        Some(values) if values.len() == 1 => format!("on day {} of the month", values[0]),
+        Some(values) => format!("on days {} of the month", join_numbers(&values)),
+        None => match field.items.as_slice() {
+            [
+                Item {
+                    base: Base::Range(start, end),
+                    step: None,
+                },
+            ] => format!("on days {start} through {end} of the month"),
+            _ => format!(
+                "{} of the month",
+                describe_items(field, "day", |v| format!("day {v}"))
+            ),
+        },
+    })
+}
+
+fn describe_day_of_week(field: &Field) -> Option<String> {
+    if is_unrestricted(field) {
+        return None;
+    }
+    let name = |v: u32| DAY_NAMES[v as usize].to_string();
+    if field.has_special() {
+        return Some(describe_special_days(field, name));
+    }
+    Some(match plain_values(field) {
+        Some(values) => {
+            let names = values.iter().map(|v| name(*v)).collect::<Vec<_>>();
+            format!("on {}", join_and(&names))
+        }
+        None => with_preposition("on", describe_items(field, "day of the week", name)),
+    })
+}
+
+fn describe_month(field: &Field) -> Option<String> {
+    if is_unrestricted(field) {
+        return None;
+    }
+    let name = |v: u32| MONTH_NAMES[v as usize - 1].to_string();
+    Some(match plain_values(field) {
+        Some(values) => {
+            let names = values.iter().map(|v| name(*v)).collect::<Vec<_>>();
+            format!("in {}", join_and(&names))
+        }
+        None => with_preposition("in", describe_items(field, "month", name)),
+    })
+}
+
+fn describe_year(field: &Field) -> Option<String> {
+    if is_unrestricted(field) {
+        return None;
+    }
+    Some(match plain_values(field) {
+        Some(values) => format!("in {}", join_numbers(&values)),
+        None => with_preposition("in", describe_items(field, "year", |v| v.to_string())),
+    })
+}
+
+/// `on the last day of the month`, `on the third Friday of the month`; plain
+/// items in the same field are labelled with `label`.
+fn describe_special_days(field: &Field, label: impl Fn(u32) -> String) -> String {
+    let parts = field
+        .items
+        .iter()
+        .map(|item| match item.base {
+            Base::Last(0) => "the last day of the month".to_string(),
+            Base::Last(1) => "the day before the last day of the month".to_string(),
+            Base::Last(offset) => {
+                format!("the day {offset} days before the last day of the month")
+            }
+            Base::LastWeekday => "the last weekday of the month".to_string(),
+            Base::NearestWeekday(day) => format!("the weekday nearest day {day} of the month"),
+            Base::Nth(day, nth) => format!(
+                "the {} {} of the month",
+                ORDINALS[nth as usize - 1],
+                DAY_NAMES[day as usize]
+            ),
+            Base::LastOf(day) => format!("the last {} of the month", DAY_NAMES[day as usize]),
+            _ => {
+                let single = Field {
+                    kind: field.kind,
+                    items: vec![*item],
+                };
+                describe_items(&single, "day", &label)
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("on {}", join_and(&parts))
+}
+
+/// Describe each list element in turn, e.g. `every 2 hours from 09:00 through 17:00`.
+fn describe_items(field: &Field, unit: &str, label: impl Fn(u32) -> String) -> String {
+    let (min, max) = field.kind.bounds();
+    let max = if field.kind == FieldKind::DayOfWeek {
+        6
+    } else {
+        max
+    };
+    let every = |step: Option<u32>| match step {
+        Some(step) if step > 1 => format!("every {step} {}", plural(unit)),
+        _ => format!("every {unit}"),
+    };
+    let parts = field
+        .items
+        .iter()
+        .map(|item| match (item.base, item.step) {
+            (Base::Any, step) => every(step),
+            (Base::Value(value), None) => label(value),
+            (Base::Value(value), step) => {
+                format!(
+                    "{} from {} through {}",
+                    every(step),
+                    label(value),
+                    label(max)
+                )
+            }
+            (Base::Range(start, end), step) if step.is_none() && start == min && end == max => {
+                every(step)
+            }
+            (Base::Range(start, end), None) if field.kind != FieldKind::Minute => {
+                format!("{} through {}", label(start), label(end))
+            }
+            (Base::Range(start, end), step) => {
+                format!(
+                    "{} from {} through {}",
+                    every(step),
+                    label(start),
+                    label(end)
+                )
+            }
+            _ => unreachable!("date-dependent items are described separately"),
+        })
+        .collect::<Vec<_>>();
+    join_and(&parts)
+}
+
+/// `on Monday through Friday`, but `every 2 days of the week` as is.
+fn with_preposition(preposition: &str, phrase: String) -> String {
+    if phrase.starts_with("every") {
+        phrase
+    } else {
+        format!("{preposition} {phrase}")
+    }
+}
+
+/// Whether a field places no restriction at all (a bare `*`).
+fn is_unrestricted(field: &Field) -> bool {
+    matches!(
+        field.items.as_slice(),
+        [Item {
+            base: Base::Any,
+            step: None
+        }]
+    )
+}
+
+/// The field's values when it is nothing but a list of single values.
+fn plain_values(field: &Field) -> Option<Vec<u32>> {
+    let mut values = field
+        .items
+        .iter()
+        .map(|item| match item {
+            Item {
+                base: Base::Value(value),
+                step: None,
+            } => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    values.sort();
+    values.dedup();
+    Some(values)
+}
+
+fn plural(unit: &str) -> String {
+    match unit {
+        "day of the week" => "days of the week".to_string(),
+        _ => format!("{unit}s"),
+    }
+}
+
+fn join_numbers(values: &[u32]) -> String {
+    join_and(&values.iter().map(u32::to_string).collect::<Vec<_>>())
+}
+
+/// `January and July`, or `June through August` for three or more months in
+/// a row.
+pub(crate) fn describe_months(months: &[u32]) -> String {
+    let name = |v: u32| MONTH_NAMES[v as usize - 1].to_string();
+    match months {
+        [first, .., last] if months.len() > 2 && last - first + 1 == months.len() as u32 => {
+            format!("{} through {}", name(*first), name(*last))
+        }
+        _ => join_and(&months.iter().map(|v| name(*v)).collect::<Vec<_>>()),
+    }
+}
+
+pub(crate) fn join_and(parts: &[String]) -> String {
+    match parts {
+        [] => String::new(),
+        [only] => only.clone(),
+        [head @ .., last] => format!("{} and {last}", head.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_each_field() {
+        for (expression, sentence) in [
+            (
+                "*/15 9-17 * * 1-5",
+                "Every 15 minutes, between 09:00 and 17:59 on Monday through Friday",
+            ),
+            ("0 */2 * * *", "At minute 0, every 2 hours"),
+            ("0 0 1,15 * *", "At 00:00 on days 1 and 15 of the month"),
+            ("0 12 * 1-3 *", "At 12:00 in January through March"),
+            ("0 9 * * 1,3,5", "At 09:00 on Monday, Wednesday and Friday"),
+            ("15 10 * * 7", "At 10:15 on Sunday"),
+        ] {
+            assert_eq!(explain(expression).unwrap(), sentence, "{expression}");
+        }
+    }
+
+    #[test]
+    fn restricted_day_fields_read_as_either() {
+        assert_eq!(
+            explain("0 0 13 * 5").unwrap(),
+            "At 00:00 on day 13 of the month or on Friday"
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_are_errors_with_spans() {
+        assert_eq!(
+            explain("0 9 * *"),
+            Err(ParseError::WrongFieldCount {
+                span: 0..7,
+                expected: "5",
+                found: 4,
+            })
+        );
+        assert!(matches!(
+            explain("0 25 * * *"),
+            Err(ParseError::FieldOutOfRange {
+                span,
+                value: 25,
+                ..
+            }) if span == (2..4)
+        ));
+    }
+}
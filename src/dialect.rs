@@ -0,0 +1,538 @@
+use crate::describe::{describe_schedule, span_of_words};
+use crate::error::{ConvertError, ParseError};
+use crate::field::{Base, Field, FieldKind, parse_field, spec_extras, spec_fields};
+use crate::parse::{looks_raw, span_in};
+use crate::spec::CronSpec;
+
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A flavour of cron syntax.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// The classic five fields, with Sunday as 0 or 7.
+    #[default]
+    Vixie,
+    /// Quartz Scheduler: seconds first, an optional year last, `?` in one of
+    /// the day fields, `L`, `W` and `#`, and Sunday as 1.
+    Quartz,
+    /// Spring's `@Scheduled`: seconds first, Quartz's `L`, `W` and `#`, and
+    /// Vixie's day numbers. There is no year.
+    Spring,
+}
+
+impl Dialect {
+    /// How errors refer to the dialect.
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::Vixie => "cron",
+            Dialect::Quartz => "Quartz",
+            Dialect::Spring => "Spring",
+        }
+    }
+}
+
+impl CronSpec {
+    /// The schedule written in `dialect`.
+    ///
+    /// Natural-language schedules come out as, e.g., `0 15 7 ? * MON-FRI` for
+    /// Quartz. Schedules the dialect can't express are an error: seconds,
+    /// years and `L`, `W` or `#` days for Vixie, and for Quartz a schedule
+    /// restricting both day-of-month and day-of-week. A schedule of several
+    /// lines comes out one per line.
+    ///
+    /// ```
+    /// use cronoisseur::{Dialect, parse_expression};
+    ///
+    /// let spec = parse_expression("weekdays at 07:15")?;
+    /// assert_eq!(spec.to_dialect(Dialect::Quartz)?, "0 15 7 ? * MON-FRI");
+    /// assert_eq!(spec.to_dialect(Dialect::Spring)?, "0 15 7 * * MON-FRI");
+    /// # Ok::<(), cronoisseur::ConvertError>(())
+    /// ```
+    pub fn to_dialect(&self, dialect: Dialect) -> Result<String, ConvertError> {
+        if !self.extra_lines.is_empty() {
+            let lines = self
+                .lines()
+                .iter()
+                .map(|line| line.to_dialect(dialect))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(lines.join("\n"));
+        }
+        let layout = match dialect {
+            Dialect::Vixie if self.is_reboot() => return Ok(self.as_string()),
+            Dialect::Vixie => {
+                let fields = vixie_fields(self, dialect.name())?;
+                if fields.iter().all(|field| field.vixie_steps().is_none()) {
+                    return Ok(self.as_string());
+                }
+                let texts = [
+                    &self.minute,
+                    &self.hour,
+                    &self.day_of_month,
+                    &self.month,
+                    &self.day_of_week,
+                ];
+                let fields: Vec<String> = fields
+                    .iter()
+                    .zip(texts)
+                    .map(|(field, text)| field.vixie_steps().unwrap_or_else(|| text.clone()))
+                    .collect();
+                return Ok(fields.join(" "));
+            }
+            Dialect::Quartz => &QUARTZ,
+            Dialect::Spring => &SPRING,
+        };
+        Ok(quartz_fields(self, layout)?.join(" "))
+    }
+}
+
+/// Why `@reboot` has no equivalent outside of a crontab.
+pub(crate) const STARTUP: &str = "it runs at startup, not on a calendar";
+
+/// How a syntax from the Quartz family lays out its fields.
+pub(crate) struct Layout {
+    pub(crate) name: &'static str,
+    /// Day-of-week number for Sunday; the rest follow in order.
+    sunday: u32,
+    seconds: bool,
+    year: YearField,
+    /// Whether the unrestricted day field is written `?`, which leaves no
+    /// way to restrict both.
+    question_mark: bool,
+    /// Whether stepped wildcards are written from their first value (`0/15`).
+    explicit_step_start: bool,
+}
+
+#[derive(PartialEq, Eq)]
+enum YearField {
+    Absent,
+    Optional,
+    Required,
+}
+
+const QUARTZ: Layout = Layout {
+    name: "Quartz",
+    sunday: 1,
+    seconds: true,
+    year: YearField::Optional,
+    question_mark: true,
+    explicit_step_start: false,
+};
+
+const SPRING: Layout = Layout {
+    name: "Spring",
+    sunday: 0,
+    seconds: true,
+    year: YearField::Absent,
+    question_mark: false,
+    explicit_step_start: false,
+};
+
+pub(crate) const EVENTBRIDGE: Layout = Layout {
+    name: "EventBridge",
+    sunday: 1,
+    seconds: false,
+    year: YearField::Required,
+    question_mark: true,
+    explicit_step_start: true,
+};
+
+/// The schedule's fields in a Quartz-style `layout`, or why it can't be
+/// written there.
+pub(crate) fn quartz_fields(spec: &CronSpec, layout: &Layout) -> Result<Vec<String>, ConvertError> {
+    let fail = |reason: &str| ConvertError::Inexpressible {
+        cron: spec.full_string(),
+        target: layout.name.to_string(),
+        reason: reason.to_string(),
+    };
+    if spec.is_reboot() {
+        return Err(fail(STARTUP));
+    }
+    let [minute, hour, day_of_month, month, day_of_week] = spec_fields(spec)?;
+    let (second, year) = spec_extras(spec)?;
+
+    let dom_free = day_of_month.is_full();
+    let dow_free = day_of_week.is_full();
+    let either = !day_of_month.is_star() && !day_of_week.is_star();
+    if !dom_free && !dow_free && (layout.question_mark || either) {
+        return Err(fail(if either {
+            "it runs when either day-of-month or day-of-week matches"
+        } else {
+            "it restricts both day-of-month and day-of-week"
+        }));
+    }
+    let unrestricted = if layout.question_mark { "?" } else { "*" };
+    let text = |field: &Field| {
+        if layout.explicit_step_start {
+            explicit_step_start(field)
+        } else {
+            field.canonical()
+        }
+    };
+    let (dom, dow) = if dow_free {
+        (text(&day_of_month), unrestricted.to_string())
+    } else if dom_free && layout.question_mark {
+        (unrestricted.to_string(), weekday_text(&day_of_week, layout))
+    } else {
+        (text(&day_of_month), weekday_text(&day_of_week, layout))
+    };
+
+    let mut parts = Vec::new();
+    match second {
+        Some(second) if layout.seconds => parts.push(text(&second)),
+        None if layout.seconds => parts.push("0".to_string()),
+        Some(second) if !second.set().values().eq([0]) => {
+            return Err(fail(&format!("{} has no seconds field", layout.name)));
+        }
+        _ => {}
+    }
+    parts.extend([text(&minute), text(&hour), dom, text(&month), dow]);
+    match year.filter(year_restricted) {
+        Some(_) if layout.year == YearField::Absent => {
+            return Err(fail(&format!("{} has no year field", layout.name)));
+        }
+        Some(year) => parts.push(text(&year)),
+        None if layout.year == YearField::Required => parts.push("*".to_string()),
+        None => {}
+    }
+    Ok(parts)
+}
+
+/// The five fields of a schedule that plain five-field cron can run; seconds,
+/// years and `L`, `W` or `#` days are an error naming `target`.
+pub(crate) fn vixie_fields(spec: &CronSpec, target: &str) -> Result<[Field; 5], ConvertError> {
+    let fail = |reason: &str| ConvertError::Inexpressible {
+        cron: spec.full_string(),
+        target: target.to_string(),
+        reason: reason.to_string(),
+    };
+    if spec.is_reboot() {
+        return Err(fail(STARTUP));
+    }
+    let fields = spec_fields(spec)?;
+    let (second, year) = spec_extras(spec)?;
+    let reason = if second.is_some_and(|second| !second.set().values().eq([0])) {
+        Some("it runs at seconds past the minute")
+    } else if year.as_ref().is_some_and(year_restricted) {
+        Some("it is limited to certain years")
+    } else if fields[2].has_special() || fields[4].has_special() {
+        Some("it uses `L`, `W` or `#` days")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => Err(fail(reason)),
+        None => Ok(fields),
+    }
+}
+
+/// The field with `*/15` written as `0/15`.
+fn explicit_step_start(field: &Field) -> String {
+    let (min, _) = field.kind.bounds();
+    field
+        .canonical()
+        .split(',')
+        .map(|item| match item.strip_prefix("*/") {
+            Some(step) => format!("{min}/{step}"),
+            None => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn year_restricted(year: &Field) -> bool {
+    let (min, max) = year.kind.bounds();
+    !(min..=max).all(|value| year.allows(value))
+}
+
+/// Day-of-week with names for plain days (`MON-FRI`) and numbers in the
+/// layout's own numbering for `#` and `L`.
+fn weekday_text(field: &Field, layout: &Layout) -> String {
+    let number = |day: u32| day % 7 + layout.sunday;
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for day in field.set().values() {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == day => *end = day,
+            _ => runs.push((day, day)),
+        }
+    }
+    let mut parts: Vec<String> = runs
+        .into_iter()
+        .map(|(start, end)| {
+            let (start, end) = (WEEKDAYS[start as usize], WEEKDAYS[end as usize]);
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect();
+    for item in &field.items {
+        match item.base {
+            Base::Nth(day, nth) => parts.push(format!("{}#{nth}", number(day))),
+            Base::LastOf(day) => parts.push(format!("{}L", number(day))),
+            _ => {}
+        }
+    }
+    parts.join(",")
+}
+
+/// Recognise a raw Quartz or Spring expression and validate every field.
+///
+/// Like Vixie input, it counts as raw when its words use only the cron
+/// alphabet, the first starts with a digit or `*` and not all the others are
+/// plain words. Quartz day-of-week numbers (Sunday is 1) are renumbered to
+/// Vixie's on the way in, and `?` becomes `*`.
+pub(crate) fn try_parse_dialect(
+    input: &str,
+    dialect: Dialect,
+) -> Result<Option<CronSpec>, ParseError> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    if !looks_raw(&parts) {
+        return Ok(None);
+    }
+    let (expected, counts) = match dialect {
+        // Five-field input is `try_parse_raw`'s.
+        Dialect::Vixie => return Ok(None),
+        Dialect::Quartz => ("6 or 7", 6..=7),
+        Dialect::Spring => ("6", 6..=6),
+    };
+    if !counts.contains(&parts.len()) {
+        return Err(ParseError::WrongFieldCount {
+            span: span_of_words(input),
+            expected,
+            found: parts.len(),
+        });
+    }
+
+    // Day-of-week is renumbered in a copy of the input of the same length,
+    // so spans into it still point at the original text.
+    let dow_span = span_in(input, parts[5]);
+    let mut source = input.to_string();
+    if dialect == Dialect::Quartz {
+        source.replace_range(dow_span.clone(), &quartz_weekdays(input, parts[5])?);
+    }
+    let part = |index: usize| {
+        let span = span_in(input, parts[index]);
+        &source[span]
+    };
+    let second = parse_field(&source, FieldKind::Second, part(0), true)?;
+    let minute = parse_field(&source, FieldKind::Minute, part(1), true)?;
+    let hour = parse_field(&source, FieldKind::Hour, part(2), true)?;
+    let day_of_month = parse_field(&source, FieldKind::DayOfMonth, part(3), true)?;
+    let month = parse_field(&source, FieldKind::Month, part(4), true)?;
+    let day_of_week =
+        parse_field(&source, FieldKind::DayOfWeek, part(5), true).map_err(|err| match err {
+            ParseError::InvalidField {
+                span,
+                field,
+                reason,
+                ..
+            } => ParseError::InvalidField {
+                span,
+                field,
+                text: parts[5].to_string(),
+                reason,
+            },
+            err => err,
+        })?;
+    let year = match parts.get(6) {
+        Some(_) => Some(parse_field(&source, FieldKind::Year, part(6), true)?),
+        None => None,
+    };
+
+    let unspecified = (parts[3] == "?", parts[5] == "?");
+    let day_error = |reason| ParseError::InvalidField {
+        span: dow_span.clone(),
+        field: FieldKind::DayOfWeek.name(),
+        text: parts[5].to_string(),
+        reason,
+    };
+    if dialect == Dialect::Quartz && unspecified.0 == unspecified.1 {
+        return Err(day_error(
+            "Quartz needs `?` in exactly one of day-of-month and day-of-week",
+        ));
+    }
+    if !day_of_month.is_star() && !day_of_week.is_star() {
+        return Err(day_error(
+            "both day fields must match, so one of them has to be `*` or `?`",
+        ));
+    }
+
+    let fields = [minute, hour, day_of_month, month, day_of_week];
+    let explanation = describe_schedule(Some(&second), &fields, year.as_ref());
+    // Vixie cron has no `5/15`, so the stored fields spell it `5-59/15`.
+    let text = |index: usize| match part(index) {
+        "?" => "*".to_string(),
+        text => index
+            .checked_sub(1)
+            .and_then(|field| fields.get(field))
+            .and_then(Field::vixie_steps)
+            .unwrap_or_else(|| text.to_string()),
+    };
+    Ok(Some(CronSpec {
+        macro_name: None,
+        second: Some(text(0)),
+        minute: text(1),
+        hour: text(2),
+        day_of_month: text(3),
+        month: text(4),
+        day_of_week: text(5),
+        year: parts.get(6).map(|_| text(6)).filter(|year| year != "*"),
+        time_zone: None,
+        extra_lines: Vec::new(),
+        explanation,
+        line_explanation: None,
+        warnings: Vec::new(),
+    }))
+}
+
+/// A Quartz day-of-week field with its numbers moved to Vixie's (Sunday from
+/// 1 to 0) and a bare `L` (Saturday) spelled out, keeping every byte offset.
+fn quartz_weekdays(source: &str, field: &str) -> Result<String, ParseError> {
+    let start = span_in(source, field).start;
+    let mut out = String::with_capacity(field.len());
+    let mut digits = String::new();
+    let mut in_step = false;
+    let mut after_hash = false;
+    let flush =
+        |digits: &mut String, out: &mut String, at: usize, shift: bool| -> Result<(), ParseError> {
+            if digits.is_empty() {
+                return Ok(());
+            }
+            let value = digits.parse::<u32>().unwrap_or(u32::MAX);
+            if shift {
+                if !(1..=7).contains(&value) {
+                    return Err(ParseError::FieldOutOfRange {
+                        span: start + at - digits.len()..start + at,
+                        field: FieldKind::DayOfWeek.name(),
+                        value,
+                        min: 1,
+                        max: 7,
+                    });
+                }
+                out.push_str(&format!("{:0width$}", value - 1, width = digits.len()));
+            } else {
+                out.push_str(digits);
+            }
+            digits.clear();
+            Ok(())
+        };
+    for (at, ch) in field.char_indices() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        flush(&mut digits, &mut out, at, !in_step && !after_hash)?;
+        match ch {
+            ',' => {
+                in_step = false;
+                after_hash = false;
+            }
+            '/' => in_step = true,
+            '#' => after_hash = true,
+            _ => {}
+        }
+        let bare_last = matches!(ch, 'L' | 'l')
+            && field[..at]
+                .chars()
+                .next_back()
+                .is_none_or(|previous| previous == ',')
+            && field[at + 1..]
+                .chars()
+                .next()
+                .is_none_or(|next| next == ',' || next == '/');
+        out.push(if bare_last { '6' } else { ch });
+    }
+    flush(&mut digits, &mut out, field.len(), !in_step && !after_hash)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_expression, parse_expression_in};
+
+    fn vixie(quartz: &str) -> String {
+        parse_expression_in(quartz, Dialect::Quartz)
+            .unwrap()
+            .to_dialect(Dialect::Vixie)
+            .unwrap()
+    }
+
+    fn convert(expression: &str, dialect: Dialect) -> String {
+        parse_expression(expression)
+            .unwrap()
+            .to_dialect(dialect)
+            .unwrap()
+    }
+
+    #[test]
+    fn quartz_value_steps_become_vixie_ranges() {
+        assert_eq!(vixie("0 0/5 14 * * ?"), "*/5 14 * * *");
+        assert_eq!(vixie("0 3/5 14 * * ?"), "3-59/5 14 * * *");
+        assert_eq!(vixie("0 0 1/6 * * ?"), "0 1-23/6 * * *");
+        assert_eq!(vixie("0 0 9 ? * 2/2"), "0 9 * * 1-6/2");
+        assert_eq!(vixie("0 15 7 ? * MON-FRI"), "15 7 * * MON-FRI");
+    }
+
+    #[test]
+    fn value_steps_are_rewritten_whatever_built_the_schedule() {
+        let spec = CronSpec::new("10/20", "*", "*", "*", "*", "");
+        assert_eq!(spec.to_dialect(Dialect::Vixie).unwrap(), "10-59/20 * * * *");
+        let spec = CronSpec::new("*/20", "9-17", "*", "JAN", "MON", "");
+        assert_eq!(
+            spec.to_dialect(Dialect::Vixie).unwrap(),
+            "*/20 9-17 * JAN MON"
+        );
+    }
+
+    #[test]
+    fn vixie_schedules_gain_seconds_and_day_markers() {
+        assert_eq!(
+            convert("weekdays at 07:15", Dialect::Quartz),
+            "0 15 7 ? * MON-FRI"
+        );
+        assert_eq!(
+            convert("weekdays at 07:15", Dialect::Spring),
+            "0 15 7 * * MON-FRI"
+        );
+        assert_eq!(
+            convert("on the 1st of january and july", Dialect::Quartz),
+            "0 0 0 1 1,7 ?"
+        );
+    }
+
+    #[test]
+    fn quartz_weekdays_count_from_sunday_as_one() {
+        assert_eq!(
+            convert("last friday of the month at 09:00", Dialect::Quartz),
+            "0 0 9 ? * 6L"
+        );
+        assert_eq!(
+            convert("last friday of the month at 09:00", Dialect::Spring),
+            "0 0 9 * * 5L"
+        );
+        let spec = parse_expression_in("0 0 12 ? * 2#1 2027", Dialect::Quartz).unwrap();
+        assert_eq!(spec.day_of_week, "1#1");
+        assert_eq!(
+            spec.to_dialect(Dialect::Quartz).unwrap(),
+            "0 0 12 ? * 2#1 2027"
+        );
+    }
+
+    #[test]
+    fn what_the_target_lacks_is_an_error() {
+        let spec = parse_expression_in("30 0 12 * * ?", Dialect::Quartz).unwrap();
+        let err = spec.to_dialect(Dialect::Vixie).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("it runs at seconds past the minute"),
+            "{err}"
+        );
+        let reboot = parse_expression("@reboot").unwrap();
+        assert!(reboot.to_dialect(Dialect::Quartz).is_err());
+        assert_eq!(reboot.to_dialect(Dialect::Vixie).unwrap(), "@reboot");
+        let err = parse_expression_in("0 0 12 1 * 2", Dialect::Quartz).unwrap_err();
+        assert!(matches!(err, ParseError::InvalidField { .. }), "{err}");
+    }
+}
@@ -0,0 +1,237 @@
+use crate::describe::join_and;
+use crate::error::ParseError;
+use crate::next::Fields;
+use crate::spec::CronSpec;
+use crate::zone::offset_minutes;
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::fmt;
+
+/// What a daylight saving change does to the runs caught in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DstEffect {
+    /// Clocks jump forward over the runs, so they don't happen.
+    Skipped,
+    /// Clocks go back over the runs, so they happen twice.
+    Repeated,
+}
+
+/// Runs on one day that a daylight saving change skips or repeats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DstHazard {
+    pub effect: DstEffect,
+    pub zone: String,
+    /// Local time the clocks change at.
+    pub at: NaiveDateTime,
+    /// The wall-clock span that is missing or happens twice.
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Wall-clock times of the affected runs.
+    pub runs: Vec<NaiveTime>,
+}
+
+impl fmt::Display for DstHazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = if self.runs.iter().any(|run| run.second() != 0) {
+            "%H:%M:%S"
+        } else {
+            "%H:%M"
+        };
+        let times: Vec<String> = self
+            .runs
+            .iter()
+            .map(|run| run.format(format).to_string())
+            .collect();
+        let runs = match times.as_slice() {
+            [only] => format!("the {only} run"),
+            [first, .., last] if times.len() > 3 => {
+                format!("the {} runs from {first} to {last}", times.len())
+            }
+            _ => format!("the {} runs", join_and(&times)),
+        };
+        let (start, end) = (self.start.format("%H:%M"), self.end.format("%H:%M"));
+        let (change, outcome) = match self.effect {
+            DstEffect::Skipped => (format!("jump from {start} to {end}"), "skipped"),
+            DstEffect::Repeated => (format!("go back from {end} to {start}"), "twice"),
+        };
+        let verb = match (self.effect, self.runs.len()) {
+            (DstEffect::Skipped, 1) => "is",
+            (DstEffect::Skipped, _) => "are",
+            (DstEffect::Repeated, 1) => "happens",
+            (DstEffect::Repeated, _) => "happen",
+        };
+        write!(
+            f,
+            "On {} clocks in {} {change}, so {runs} {verb} {outcome}; schedule it before {start} or from {end}, or in UTC",
+            self.at.format("%Y-%m-%d"),
+            self.zone
+        )
+    }
+}
+
+/// Daylight saving changes in `zone` during the `days` after `from` that
+/// skip or repeat a run of `spec`, read as wall-clock time in `zone`.
+///
+/// Schedules that fire every hour are left alone, since a change costs or
+/// adds one run among many rather than the day's only one.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use cronoisseur::{DstEffect, dst_hazards, parse_expression};
+///
+/// let spec = parse_expression("daily at 02:30")?;
+/// let from = Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap();
+/// let hazards = dst_hazards(&spec, chrono_tz::America::New_York, from, 365)?;
+/// assert_eq!(hazards[0].effect, DstEffect::Skipped);
+/// assert_eq!(hazards[0].at.to_string(), "2027-03-14 02:00:00");
+/// # Ok::<(), cronoisseur::ParseError>(())
+/// ```
+pub fn dst_hazards(
+    spec: &CronSpec,
+    zone: Tz,
+    from: DateTime<Utc>,
+    days: i64,
+) -> Result<Vec<DstHazard>, ParseError> {
+    if spec.is_reboot() {
+        return Ok(Vec::new());
+    }
+    let lines = spec
+        .lines()
+        .iter()
+        .map(Fields::from_spec)
+        .collect::<Result<Vec<_>, _>>()?;
+    if lines
+        .iter()
+        .any(|fields| fields.hour.values().count() == 24)
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut hazards = Vec::new();
+    for (instant, before, after) in transitions(zone, from, from + Duration::days(days)) {
+        let local = |offset: i32| instant.naive_utc() + Duration::minutes(offset.into());
+        let (effect, start, end) = if after > before {
+            (DstEffect::Skipped, local(before), local(after))
+        } else {
+            (DstEffect::Repeated, local(after), local(before))
+        };
+        let mut runs = Vec::new();
+        for date in start
+            .date()
+            .iter_days()
+            .take_while(|date| *date <= end.date())
+        {
+            for fields in lines.iter().filter(|fields| fields.day_matches(date)) {
+                for hour in fields.hour.values() {
+                    for minute in fields.minute.values() {
+                        for second in fields.second.values() {
+                            if let Some(run) = date.and_hms_opt(hour, minute, second)
+                                && (start..end).contains(&run)
+                            {
+                                runs.push(run.time());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        runs.sort();
+        runs.dedup();
+        if !runs.is_empty() {
+            hazards.push(DstHazard {
+                effect,
+                zone: zone.name().to_string(),
+                at: local(before),
+                start: start.time(),
+                end: end.time(),
+                runs,
+            });
+        }
+    }
+    Ok(hazards)
+}
+
+/// Each instant in `from..until` where `zone`'s UTC offset changes, with the
+/// offsets in minutes before and after it.
+fn transitions(
+    zone: Tz,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, i32, i32)> {
+    let mut found = Vec::new();
+    let mut at = from;
+    let mut offset = offset_minutes(zone, at);
+    while at < until {
+        let next = at + Duration::hours(1);
+        let next_offset = offset_minutes(zone, next);
+        if next_offset != offset {
+            // Changes happen on the minute; narrow the hour down to it.
+            let (mut low, mut high) = (at, next);
+            while high - low > Duration::minutes(1) {
+                let middle = low + (high - low) / 2;
+                if offset_minutes(zone, middle) == offset {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            let change = high.duration_trunc(Duration::minutes(1)).unwrap_or(high);
+            found.push((change, offset, next_offset));
+        }
+        at = next;
+        offset = next_offset;
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+    use chrono::TimeZone;
+    use chrono_tz::Europe::Berlin;
+
+    fn hazards(expression: &str, zone: Tz) -> Vec<DstHazard> {
+        let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        dst_hazards(&parse_expression(expression).unwrap(), zone, from, 365).unwrap()
+    }
+
+    #[test]
+    fn runs_in_both_changes_are_reported() {
+        let found = hazards("30 2 * * *", Berlin);
+        let summary: Vec<(DstEffect, String)> = found
+            .iter()
+            .map(|hazard| (hazard.effect, hazard.at.to_string()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (DstEffect::Skipped, "2026-03-29 02:00:00".to_string()),
+                (DstEffect::Repeated, "2026-10-25 03:00:00".to_string()),
+            ]
+        );
+        assert_eq!(
+            found[0].to_string(),
+            "On 2026-03-29 clocks in Europe/Berlin jump from 02:00 to 03:00, so the 02:30 run is skipped; schedule it before 02:00 or from 03:00, or in UTC"
+        );
+        assert_eq!(
+            found[1].to_string(),
+            "On 2026-10-25 clocks in Europe/Berlin go back from 03:00 to 02:00, so the 02:30 run happens twice; schedule it before 02:00 or from 03:00, or in UTC"
+        );
+    }
+
+    #[test]
+    fn runs_clear_of_the_changes_are_safe() {
+        assert!(hazards("0 4 * * *", Berlin).is_empty());
+        assert!(hazards("30 2 * * *", chrono_tz::Asia::Tokyo).is_empty());
+        assert!(hazards("30 2 * * 1-5", Berlin).is_empty());
+    }
+
+    #[test]
+    fn hourly_schedules_are_left_alone() {
+        assert!(hazards("15 * * * *", Berlin).is_empty());
+        assert!(hazards("@reboot", Berlin).is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::parse::capitalize;
+use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Reasons an expression could not be turned into a [`CronSpec`](crate::CronSpec).
+///
+/// Variants that point at a specific part of the input carry its byte span
+/// within the original expression, so callers can underline the offending text.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    #[error("The expression is empty")]
+    Empty,
+    #[error("`{text}` is not a valid time")]
+    BadTime { span: Range<usize>, text: String },
+    #[error("Hour {value} is out of range {max_label}")]
+    HourOutOfRange {
+        span: Range<usize>,
+        value: u32,
+        max_label: &'static str,
+    },
+    #[error("Minute {value} is out of range 0-59")]
+    MinuteOutOfRange { span: Range<usize>, value: u32 },
+    #[error("Unknown weekday `{token}`{}", did_you_mean(suggestion))]
+    UnknownWeekday {
+        span: Range<usize>,
+        token: String,
+        suggestion: Option<&'static str>,
+    },
+    #[error("Unknown month `{token}`{}", did_you_mean(suggestion))]
+    UnknownMonth {
+        span: Range<usize>,
+        token: String,
+        suggestion: Option<&'static str>,
+    },
+    #[error("Day of month {value} is out of range 1-31")]
+    DayOfMonthOutOfRange { span: Range<usize>, value: u32 },
+    #[error("There is no day {day} in {months}")]
+    NoSuchDate {
+        span: Range<usize>,
+        day: u32,
+        months: String,
+    },
+    #[error("Invalid {field} field `{text}`: {reason}")]
+    InvalidField {
+        span: Range<usize>,
+        field: &'static str,
+        text: String,
+        reason: &'static str,
+    },
+    #[error("{} {value} is out of range {min}-{max}", capitalize(field))]
+    FieldOutOfRange {
+        span: Range<usize>,
+        field: &'static str,
+        value: u32,
+        min: u32,
+        max: u32,
+    },
+    #[error("Unknown schedule macro `{name}`")]
+    UnknownMacro { span: Range<usize>, name: String },
+    #[error("Unknown time zone `{name}`")]
+    UnknownTimeZone { span: Range<usize>, name: String },
+    #[error("Expected {expected} cron fields, found {found}")]
+    WrongFieldCount {
+        span: Range<usize>,
+        expected: &'static str,
+        found: usize,
+    },
+    #[error("Unsupported phrasing. Use flag --list-patterns to list all supported shapes.")]
+    Unsupported { span: Range<usize> },
+}
+
+impl ParseError {
+    /// Byte range of the offending text within the parsed expression.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            ParseError::Empty => None,
+            ParseError::BadTime { span, .. }
+            | ParseError::HourOutOfRange { span, .. }
+            | ParseError::MinuteOutOfRange { span, .. }
+            | ParseError::UnknownWeekday { span, .. }
+            | ParseError::UnknownMonth { span, .. }
+            | ParseError::DayOfMonthOutOfRange { span, .. }
+            | ParseError::NoSuchDate { span, .. }
+            | ParseError::InvalidField { span, .. }
+            | ParseError::FieldOutOfRange { span, .. }
+            | ParseError::UnknownMacro { span, .. }
+            | ParseError::UnknownTimeZone { span, .. }
+            | ParseError::WrongFieldCount { span, .. }
+            | ParseError::Unsupported { span } => Some(span.clone()),
+        }
+    }
+
+    /// A close match for a misspelled token, if one was found.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            ParseError::UnknownWeekday { suggestion, .. }
+            | ParseError::UnknownMonth { suggestion, .. } => *suggestion,
+            _ => None,
+        }
+    }
+
+    pub(crate) fn map_span(mut self, map: impl Fn(Range<usize>) -> Range<usize>) -> Self {
+        match &mut self {
+            ParseError::Empty => {}
+            ParseError::BadTime { span, .. }
+            | ParseError::HourOutOfRange { span, .. }
+            | ParseError::MinuteOutOfRange { span, .. }
+            | ParseError::UnknownWeekday { span, .. }
+            | ParseError::UnknownMonth { span, .. }
+            | ParseError::DayOfMonthOutOfRange { span, .. }
+            | ParseError::NoSuchDate { span, .. }
+            | ParseError::InvalidField { span, .. }
+            | ParseError::FieldOutOfRange { span, .. }
+            | ParseError::UnknownMacro { span, .. }
+            | ParseError::UnknownTimeZone { span, .. }
+            | ParseError::WrongFieldCount { span, .. }
+            | ParseError::Unsupported { span } => *span = map(span.clone()),
+        }
+        self
+    }
+}
+
+fn did_you_mean(suggestion: &Option<&'static str>) -> String {
+    suggestion
+        .map(|word| format!("; did you mean `{word}`?"))
+        .unwrap_or_default()
+}
+
+/// Reasons a schedule could not be carried over to another zone or platform.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConvertError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("Can't convert `{cron}` to {target}: {reason}")]
+    Inexpressible {
+        cron: String,
+        target: String,
+        reason: String,
+    },
+}
+
+/// Errors returned by the library.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Convert(#[from] ConvertError),
+    #[error("{action} {}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("`{command}` failed: {message}")]
+    Command { command: String, message: String },
+    #[error(
+        "The crontab already has an entry with id `{id}`; pass a different --id, or use `update {id}` to change it"
+    )]
+    DuplicateId { id: String },
+}
+
+impl Error {
+    pub(crate) fn io(
+        action: &'static str,
+        path: impl Into<PathBuf>,
+    ) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Error::Io {
+            action,
+            path,
+            source,
+        }
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
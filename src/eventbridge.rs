@@ -0,0 +1,176 @@
+use crate::dialect::{EVENTBRIDGE, quartz_fields, year_restricted};
+use crate::error::ConvertError;
+use crate::field::{Base, Field, Item, spec_extras, spec_fields};
+use crate::spec::CronSpec;
+use crate::zone::is_utc;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// An EventBridge schedule expression plus anything worth knowing about it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventBridgeSchedule {
+    pub expression: String,
+    pub warnings: Vec<String>,
+}
+
+/// Write `spec` as an EventBridge `rate()` or `cron()` expression.
+///
+/// Every N minutes or hours becomes `rate()` when N divides the day evenly,
+/// so the interval never resets, including ones like every 90 minutes that
+/// cron spreads over several lines; everything else becomes a
+/// six-field `cron()` with `?` in the unused day field, weekdays numbered
+/// from Sunday as 1 and a year. Schedules EventBridge can't express, such as
+/// ones with seconds, both day fields restricted, a time zone or several
+/// lines, are an error.
+///
+/// ```
+/// use cronoisseur::{parse_expression, render_eventbridge};
+///
+/// let every = render_eventbridge(&parse_expression("every 15 minutes")?)?;
+/// assert_eq!(every.expression, "rate(15 minutes)");
+/// let spread = render_eventbridge(&parse_expression("every 90 minutes")?)?;
+/// assert_eq!(spread.expression, "rate(90 minutes)");
+/// let weekdays = render_eventbridge(&parse_expression("weekdays at 07:15")?)?;
+/// assert_eq!(weekdays.expression, "cron(15 7 ? * MON-FRI *)");
+/// # Ok::<(), cronoisseur::ConvertError>(())
+/// ```
+pub fn render_eventbridge(spec: &CronSpec) -> Result<EventBridgeSchedule, ConvertError> {
+    let fail = |reason: String| ConvertError::Inexpressible {
+        cron: spec.full_string(),
+        target: "EventBridge".to_string(),
+        reason,
+    };
+    if spec.zone().is_some_and(|zone| !is_utc(zone)) {
+        return Err(fail("rules are evaluated in UTC".to_string()));
+    }
+    if let Some(expression) = rate(spec)? {
+        return Ok(EventBridgeSchedule {
+            expression,
+            warnings: vec![
+                "rate() counts from when the rule is created, so runs are not aligned to the clock"
+                    .to_string(),
+            ],
+        });
+    }
+    if !spec.extra_lines.is_empty() {
+        return Err(fail(format!(
+            "it takes {} cron lines and a rule has one expression",
+            spec.lines().len()
+        )));
+    }
+    Ok(EventBridgeSchedule {
+        expression: format!("cron({})", quartz_fields(spec, &EVENTBRIDGE)?.join(" ")),
+        warnings: Vec::new(),
+    })
+}
+
+/// `rate(N minutes)` or `rate(N hours)` for a plain even interval, whether
+/// it takes one cron line or several.
+fn rate(spec: &CronSpec) -> Result<Option<String>, ConvertError> {
+    if spec.is_reboot() {
+        return Ok(None);
+    }
+    // Minutes after midnight of every run, across all lines.
+    let mut times = BTreeSet::new();
+    for line in spec.lines() {
+        let [minute, hour, day_of_month, month, day_of_week] = spec_fields(&line)?;
+        let (second, year) = spec_extras(&line)?;
+        let plain = second.is_none_or(|second| second.set().values().eq([0]))
+            && !year.as_ref().is_some_and(year_restricted)
+            && [&day_of_month, &month, &day_of_week]
+                .iter()
+                .all(|field| every(field) == Some(1));
+        if !plain {
+            return Ok(None);
+        }
+        let minute = minute.set();
+        times.extend(
+            hour.set()
+                .values()
+                .flat_map(|h| minute.values().map(move |m| h * 60 + m)),
+        );
+    }
+    let times: Vec<u32> = times.into_iter().collect();
+    let [0, second, ..] = times[..] else {
+        return Ok(None);
+    };
+    let even = times.windows(2).all(|pair| pair[1] - pair[0] == second)
+        && second * times.len() as u32 == MINUTES_PER_DAY;
+    if !even {
+        return Ok(None);
+    }
+    let (amount, unit) = if second.is_multiple_of(60) {
+        (second / 60, "hour")
+    } else {
+        (second, "minute")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    Ok(Some(format!("rate({amount} {unit}{plural})")))
+}
+
+/// The step of a field that is just `*` or `*/N`.
+fn every(field: &Field) -> Option<u32> {
+    match field.items.as_slice() {
+        [
+            Item {
+                base: Base::Any,
+                step,
+            },
+        ] => Some(step.unwrap_or(1)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn render(expression: &str) -> Result<EventBridgeSchedule, ConvertError> {
+        render_eventbridge(&parse_expression(expression).unwrap())
+    }
+
+    #[test]
+    fn even_intervals_become_rates() {
+        assert_eq!(render("every hour").unwrap().expression, "rate(1 hour)");
+        assert_eq!(render("0 */6 * * *").unwrap().expression, "rate(6 hours)");
+        assert_eq!(
+            render("every 90 minutes").unwrap().warnings,
+            ["rate() counts from when the rule is created, so runs are not aligned to the clock"]
+        );
+    }
+
+    #[test]
+    fn offset_or_uneven_steps_stay_cron() {
+        assert_eq!(
+            render("5-59/15 * * * *").unwrap().expression,
+            "cron(5-59/15 * * * ? *)"
+        );
+        assert_eq!(
+            render("*/45 * * * *").unwrap().expression,
+            "cron(0/45 * * * ? *)"
+        );
+        assert_eq!(
+            render("on the 1st of january and july").unwrap().expression,
+            "cron(0 0 1 1,7 ? *)"
+        );
+    }
+
+    #[test]
+    fn zones_and_several_lines_are_errors() {
+        let err = render("daily at 09:00 Europe/Berlin").unwrap_err();
+        assert!(
+            err.to_string().contains("rules are evaluated in UTC"),
+            "{err}"
+        );
+        let err = render("daily at 09:00 and 17:30").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("it takes 2 cron lines and a rule has one expression"),
+            "{err}"
+        );
+        assert!(render("daily at 09:00 UTC").is_ok());
+    }
+}
@@ -0,0 +1,631 @@
+use crate::error::ParseError;
+use crate::parse::span_in;
+use crate::spec::CronSpec;
+use std::ops::Range;
+
+/// One of the positions in a cron expression. `Second` and `Year` only
+/// exist in the Quartz and Spring dialects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    Second,
+    Minute,
+    Hour,
+    DayOfMonth,
+    Month,
+    DayOfWeek,
+    Year,
+}
+
+impl FieldKind {
+    pub(crate) const ALL: [FieldKind; 5] = [
+        FieldKind::Minute,
+        FieldKind::Hour,
+        FieldKind::DayOfMonth,
+        FieldKind::Month,
+        FieldKind::DayOfWeek,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            FieldKind::Second => "second",
+            FieldKind::Minute => "minute",
+            FieldKind::Hour => "hour",
+            FieldKind::DayOfMonth => "day-of-month",
+            FieldKind::Month => "month",
+            FieldKind::DayOfWeek => "day-of-week",
+            FieldKind::Year => "year",
+        }
+    }
+
+    /// Inclusive bounds. Day-of-week accepts 7 as an alias for Sunday.
+    pub(crate) fn bounds(self) -> (u32, u32) {
+        match self {
+            FieldKind::Second | FieldKind::Minute => (0, 59),
+            FieldKind::Hour => (0, 23),
+            FieldKind::DayOfMonth => (1, 31),
+            FieldKind::Month => (1, 12),
+            FieldKind::DayOfWeek => (0, 7),
+            FieldKind::Year => (1970, 2099),
+        }
+    }
+
+    /// Where a step from a single value stops: the top of the range, except
+    /// that day-of-week stops at Saturday rather than wrapping to Sunday.
+    fn step_end(self) -> u32 {
+        match self {
+            FieldKind::DayOfWeek => 6,
+            kind => kind.bounds().1,
+        }
+    }
+
+    fn alias(self, token: &str) -> Option<u32> {
+        let names: &[&str] = match self {
+            FieldKind::Month => &[
+                "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+            ],
+            FieldKind::DayOfWeek => &["sun", "mon", "tue", "wed", "thu", "fri", "sat"],
+            _ => return None,
+        };
+        let offset = if self == FieldKind::Month { 1 } else { 0 };
+        names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(token))
+            .map(|index| index as u32 + offset)
+    }
+}
+
+/// One comma-separated element of a field, e.g. `*/15`, `1-5` or `mon`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Item {
+    pub(crate) base: Base,
+    pub(crate) step: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Base {
+    /// `*` (or `?`)
+    Any,
+    Value(u32),
+    Range(u32, u32),
+    /// `L` or `L-3` in day-of-month: the last day, or that many days before it.
+    Last(u32),
+    /// `LW` in day-of-month: the last weekday (Monday to Friday).
+    LastWeekday,
+    /// `15W` in day-of-month: the weekday nearest that day, within the month.
+    NearestWeekday(u32),
+    /// `5#3` in day-of-week: the third Friday of the month.
+    Nth(u32, u32),
+    /// `5L` in day-of-week: the last Friday of the month.
+    LastOf(u32),
+}
+
+impl Base {
+    /// Whether the item depends on the date as a whole rather than one value.
+    pub(crate) fn is_special(self) -> bool {
+        !matches!(self, Base::Any | Base::Value(_) | Base::Range(..))
+    }
+}
+
+/// A parsed cron field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Field {
+    pub(crate) kind: FieldKind,
+    pub(crate) items: Vec<Item>,
+}
+
+/// Why a field was rejected, with the byte range of the culprit within the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FieldError {
+    OutOfRange {
+        span: Range<usize>,
+        value: u32,
+    },
+    Invalid {
+        span: Range<usize>,
+        reason: &'static str,
+    },
+}
+
+impl Field {
+    /// Parse a field such as `*/15`, `1-5`, `mon,wed` or `9-17/2`.
+    ///
+    /// With `extended`, day-of-month also takes `L`, `L-n`, `LW` and `nW` and
+    /// day-of-week takes `n#k` and `nL`, and a single value takes a step
+    /// (`5/15`, from 5 to the top of the range), as in Quartz. Day-of-week
+    /// numbers stay Vixie's (0 or 7 is Sunday) either way.
+    pub(crate) fn parse(kind: FieldKind, text: &str, extended: bool) -> Result<Self, FieldError> {
+        let (min, max) = kind.bounds();
+        let mut items = Vec::new();
+        let mut offset = 0;
+        for raw in text.split(',') {
+            let span = offset..offset + raw.len();
+            offset += raw.len() + 1;
+            let invalid = |reason| FieldError::Invalid {
+                span: span.clone(),
+                reason,
+            };
+            if raw.is_empty() {
+                return Err(invalid("empty list element"));
+            }
+
+            let (range, step) = match raw.split_once('/') {
+                Some((range, step)) => {
+                    let step = step
+                        .parse::<u32>()
+                        .map_err(|_| invalid("step must be a number"))?;
+                    if step == 0 {
+                        return Err(invalid("step must be at least 1"));
+                    }
+                    (range, Some(step))
+                }
+                None => (raw, None),
+            };
+            let value_at = |token: &str, at: usize| -> Result<u32, FieldError> {
+                let token_span = span.start + at..span.start + at + token.len();
+                let value = token
+                    .parse::<u32>()
+                    .ok()
+                    .or_else(|| kind.alias(token))
+                    .ok_or(FieldError::Invalid {
+                        span: token_span.clone(),
+                        reason: "expected a number or name",
+                    })?;
+                if value < min || value > max {
+                    return Err(FieldError::OutOfRange {
+                        span: token_span,
+                        value,
+                    });
+                }
+                Ok(value)
+            };
+
+            if extended && let Some(base) = special(kind, range, span.start, &value_at)? {
+                if step.is_some() {
+                    return Err(invalid("`L`, `W` and `#` can't take a step"));
+                }
+                items.push(Item { base, step });
+                continue;
+            }
+            let base = match range {
+                "*" => Base::Any,
+                "?" if matches!(kind, FieldKind::DayOfMonth | FieldKind::DayOfWeek) => Base::Any,
+                _ => match range.split_once('-') {
+                    Some((start, end)) => {
+                        let start_value = value_at(start, 0)?;
+                        let end_value = value_at(end, start.len() + 1)?;
+                        if start_value > end_value {
+                            return Err(invalid("range start is after its end"));
+                        }
+                        Base::Range(start_value, end_value)
+                    }
+                    None if step.is_some() && !extended => {
+                        return Err(invalid(
+                            "a single value can't take a step; use a range such as `5-59/15`",
+                        ));
+                    }
+                    None => Base::Value(value_at(range, 0)?),
+                },
+            };
+            items.push(Item { base, step });
+        }
+        Ok(Self { kind, items })
+    }
+
+    /// Whether any item is one of the date-dependent forms (`L`, `W`, `#`).
+    pub(crate) fn has_special(&self) -> bool {
+        self.items.iter().any(|item| item.base.is_special())
+    }
+
+    /// Whether `value` is allowed, ignoring date-dependent items. Unlike
+    /// [`Field::set`] this works for years.
+    pub(crate) fn allows(&self, value: u32) -> bool {
+        let (min, max) = self.kind.bounds();
+        self.items.iter().any(|item| {
+            let (start, end) = match item.base {
+                Base::Any => (min, max),
+                Base::Value(start) if item.step.is_some() => (start, self.kind.step_end()),
+                Base::Value(start) => (start, start),
+                Base::Range(start, end) => (start, end),
+                _ => return false,
+            };
+            (start..=end).contains(&value) && (value - start).is_multiple_of(item.step.unwrap_or(1))
+        })
+    }
+
+    /// Whether the field starts with `*`, which Vixie cron uses to decide how
+    /// day-of-month and day-of-week combine.
+    pub(crate) fn is_star(&self) -> bool {
+        self.items
+            .first()
+            .is_some_and(|item| item.base == Base::Any)
+    }
+
+    /// Whether every value in the field's range is allowed.
+    pub(crate) fn is_full(&self) -> bool {
+        let (min, max) = self.kind.bounds();
+        let max = if self.kind == FieldKind::DayOfWeek {
+            6
+        } else {
+            max
+        };
+        let set = self.set();
+        (min..=max).all(|value| set.contains(value))
+    }
+
+    /// The field written back out with numbers only, e.g. `MON-FRI` as `1-5`.
+    pub(crate) fn canonical(&self) -> String {
+        let value = |v: u32| {
+            if self.kind == FieldKind::DayOfWeek && v == 7 {
+                0
+            } else {
+                v
+            }
+        };
+        self.items
+            .iter()
+            .map(|item| {
+                let base = match item.base {
+                    Base::Any => "*".to_string(),
+                    Base::Value(v) => value(v).to_string(),
+                    Base::Range(start, end) => format!("{start}-{end}"),
+                    Base::Last(0) => "L".to_string(),
+                    Base::Last(offset) => format!("L-{offset}"),
+                    Base::LastWeekday => "LW".to_string(),
+                    Base::NearestWeekday(day) => format!("{day}W"),
+                    Base::Nth(day, nth) => format!("{}#{nth}", value(day)),
+                    Base::LastOf(day) => format!("{}L", value(day)),
+                };
+                match item.step {
+                    Some(step) => format!("{base}/{step}"),
+                    None => base,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The field with each stepped single value written as the range Vixie
+    /// cron needs, `5/15` as `5-59/15` and `0/5` as `*/5`, or `None` when it
+    /// has no such item.
+    pub(crate) fn vixie_steps(&self) -> Option<String> {
+        let (min, _) = self.kind.bounds();
+        let mut rewritten = false;
+        let items = self
+            .items
+            .iter()
+            .map(|item| match *item {
+                Item {
+                    base: Base::Value(start),
+                    step: Some(step),
+                } => {
+                    rewritten = true;
+                    let base = if start == min {
+                        Base::Any
+                    } else {
+                        Base::Range(start, self.kind.step_end())
+                    };
+                    Item {
+                        base,
+                        step: Some(step),
+                    }
+                }
+                item => item,
+            })
+            .collect();
+        let field = Field {
+            kind: self.kind,
+            items,
+        };
+        rewritten.then(|| field.canonical())
+    }
+
+    /// The plain values the field allows; date-dependent items are left out.
+    pub(crate) fn set(&self) -> FieldSet {
+        debug_assert_ne!(self.kind, FieldKind::Year, "years don't fit a FieldSet");
+        let (min, max) = self.kind.bounds();
+        let mut mask = 0u64;
+        for item in &self.items {
+            let (start, end) = match item.base {
+                Base::Any => (min, max),
+                Base::Value(value) if item.step.is_some() => (value, self.kind.step_end()),
+                Base::Value(value) => (value, value),
+                Base::Range(start, end) => (start, end),
+                _ => continue,
+            };
+            for v in (start..=end).step_by(item.step.unwrap_or(1) as usize) {
+                mask |= 1 << v;
+            }
+        }
+        if self.kind == FieldKind::DayOfWeek && mask & (1 << 7) != 0 {
+            mask = (mask & !(1 << 7)) | 1;
+        }
+        FieldSet {
+            mask,
+            star: self.is_star(),
+        }
+    }
+}
+
+/// The Quartz day forms, or `None` when `text` is an ordinary item. `start`
+/// is the item's offset within the field, for errors.
+fn special(
+    kind: FieldKind,
+    text: &str,
+    start: usize,
+    value_at: &impl Fn(&str, usize) -> Result<u32, FieldError>,
+) -> Result<Option<Base>, FieldError> {
+    let last = ['L', 'l'];
+    match kind {
+        FieldKind::DayOfMonth => {
+            if text.eq_ignore_ascii_case("L") {
+                return Ok(Some(Base::Last(0)));
+            }
+            if text.eq_ignore_ascii_case("LW") {
+                return Ok(Some(Base::LastWeekday));
+            }
+            if let Some(offset) = text
+                .strip_prefix(last)
+                .and_then(|rest| rest.strip_prefix('-'))
+            {
+                let offset = value_at(offset, 2)?;
+                if offset > 30 {
+                    return Err(FieldError::OutOfRange {
+                        span: start + 2..start + text.len(),
+                        value: offset,
+                    });
+                }
+                return Ok(Some(Base::Last(offset)));
+            }
+            if let Some(day) = text.strip_suffix(['W', 'w']) {
+                return Ok(Some(Base::NearestWeekday(value_at(day, 0)?)));
+            }
+        }
+        FieldKind::DayOfWeek => {
+            if let Some((day, nth)) = text.split_once('#') {
+                let day = value_at(day, 0)?;
+                let nth = nth
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|nth| (1..=5).contains(nth))
+                    .ok_or(FieldError::Invalid {
+                        span: start + text.len() - nth.len()..start + text.len(),
+                        reason: "`#` must be followed by 1 to 5",
+                    })?;
+                return Ok(Some(Base::Nth(day, nth)));
+            }
+            if let Some(day) = text.strip_suffix(last)
+                && !day.is_empty()
+            {
+                return Ok(Some(Base::LastOf(value_at(day, 0)?)));
+            }
+        }
+        _ => {}
+    }
+    Ok(None)
+}
+
+/// The set of values a single cron field allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FieldSet {
+    mask: u64,
+    pub(crate) star: bool,
+}
+
+impl FieldSet {
+    pub(crate) fn single(value: u32) -> Self {
+        Self {
+            mask: 1 << value,
+            star: false,
+        }
+    }
+
+    pub(crate) fn contains(&self, value: u32) -> bool {
+        value < 64 && self.mask & (1 << value) != 0
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..64).filter(|v| self.contains(*v))
+    }
+}
+
+/// The shortest field text for a set of values: `*`, `*/15`, `9-17`,
+/// `1-23/2` or a list such as `1,3-5,9`.
+pub(crate) fn compress(kind: FieldKind, values: &[u32]) -> String {
+    let (min, max) = kind.bounds();
+    let max = if kind == FieldKind::DayOfWeek { 6 } else { max };
+    let mut values = values.to_vec();
+    values.sort_unstable();
+    values.dedup();
+    if values.len() as u32 == max - min + 1 {
+        return "*".to_string();
+    }
+    if let [first, second, third, ..] = values[..] {
+        let step = second - first;
+        let even = step > 1
+            && third - second == step
+            && values.windows(2).all(|pair| pair[1] - pair[0] == step);
+        let last = *values.last().unwrap_or(&first);
+        if even {
+            return if first == min && last + step > max {
+                format!("*/{step}")
+            } else {
+                format!("{first}-{last}/{step}")
+            };
+        }
+    }
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == value => *end = value,
+            _ => runs.push((value, value)),
+        }
+    }
+    runs.into_iter()
+        .map(|(start, end)| match end - start {
+            0 => start.to_string(),
+            1 => format!("{start},{end}"),
+            _ => format!("{start}-{end}"),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse the fields of an already-built schedule.
+///
+/// The five Vixie positions come back; the seconds and year a Quartz schedule
+/// may carry are left to [`spec_extras`].
+pub(crate) fn spec_fields(spec: &CronSpec) -> Result<[Field; 5], ParseError> {
+    let line = spec.five_fields();
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let parts: [&str; 5] = parts.try_into().map_err(|_| ParseError::Unsupported {
+        span: 0..line.len(),
+    })?;
+    parse_fields(&line, parts, true)
+}
+
+/// The seconds and year fields of a schedule, when it has them.
+pub(crate) fn spec_extras(spec: &CronSpec) -> Result<(Option<Field>, Option<Field>), ParseError> {
+    let field = |kind: FieldKind, text: &Option<String>| {
+        text.as_deref()
+            .map(|text| parse_field(text, kind, text, true))
+            .transpose()
+    };
+    Ok((
+        field(FieldKind::Second, &spec.second)?,
+        field(FieldKind::Year, &spec.year)?,
+    ))
+}
+
+/// Parse the five fields of an expression. Each part must be a subslice of
+/// `source` so errors can point at it.
+pub(crate) fn parse_fields(
+    source: &str,
+    parts: [&str; 5],
+    extended: bool,
+) -> Result<[Field; 5], ParseError> {
+    let mut fields = Vec::with_capacity(parts.len());
+    for (kind, part) in FieldKind::ALL.into_iter().zip(parts) {
+        fields.push(parse_field(source, kind, part, extended)?);
+    }
+    Ok(fields
+        .try_into()
+        .expect("one field is parsed per cron position"))
+}
+
+/// Parse one field of an expression; `part` must be a subslice of `source`.
+pub(crate) fn parse_field(
+    source: &str,
+    kind: FieldKind,
+    part: &str,
+    extended: bool,
+) -> Result<Field, ParseError> {
+    let base = span_in(source, part).start;
+    let shift = |span: Range<usize>| base + span.start..base + span.end;
+    Field::parse(kind, part, extended).map_err(|err| match err {
+        FieldError::OutOfRange { span, value } => {
+            let (min, max) = kind.bounds();
+            ParseError::FieldOutOfRange {
+                span: shift(span),
+                field: kind.name(),
+                value,
+                min,
+                max,
+            }
+        }
+        FieldError::Invalid { span, reason } => ParseError::InvalidField {
+            span: shift(span),
+            field: kind.name(),
+            text: part.to_string(),
+            reason,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_single_value_takes_a_step_only_in_quartz() {
+        assert_eq!(
+            Field::parse(FieldKind::Minute, "5/15", false),
+            Err(FieldError::Invalid {
+                span: 0..4,
+                reason: "a single value can't take a step; use a range such as `5-59/15`",
+            })
+        );
+        let field = Field::parse(FieldKind::Minute, "5/15", true).unwrap();
+        assert!(field.set().values().eq([5, 20, 35, 50]));
+        assert_eq!(field.vixie_steps().as_deref(), Some("5-59/15"));
+        assert!(Field::parse(FieldKind::Minute, "5-59/15", false).is_ok());
+        assert!(Field::parse(FieldKind::Minute, "*/15", false).is_ok());
+        assert!(crate::parse_expression("5/15 * * * *").is_err());
+    }
+
+    #[test]
+    fn weekday_steps_stop_at_saturday() {
+        let field = Field::parse(FieldKind::DayOfWeek, "1/2", true).unwrap();
+        assert!(field.set().values().eq([1, 3, 5]));
+        assert!(!field.allows(7));
+        assert_eq!(field.vixie_steps().as_deref(), Some("1-6/2"));
+    }
+
+    #[test]
+    fn malformed_items_point_at_themselves() {
+        let invalid = |kind, text, extended| match Field::parse(kind, text, extended) {
+            Err(FieldError::Invalid { span, reason }) => (span, reason),
+            other => panic!("{text}: {other:?}"),
+        };
+        assert_eq!(
+            invalid(FieldKind::Minute, "1,5-1", false),
+            (2..5, "range start is after its end")
+        );
+        assert_eq!(
+            invalid(FieldKind::Minute, "*/0", false),
+            (0..3, "step must be at least 1")
+        );
+        assert_eq!(
+            invalid(FieldKind::Minute, "1,,2", false),
+            (2..2, "empty list element")
+        );
+        assert_eq!(
+            invalid(FieldKind::DayOfMonth, "L", false),
+            (0..1, "expected a number or name")
+        );
+        assert_eq!(
+            invalid(FieldKind::DayOfWeek, "5L/2", true),
+            (0..4, "`L`, `W` and `#` can't take a step")
+        );
+    }
+
+    #[test]
+    fn values_outside_the_field_are_out_of_range() {
+        assert_eq!(
+            Field::parse(FieldKind::Minute, "0,60", false),
+            Err(FieldError::OutOfRange {
+                span: 2..4,
+                value: 60,
+            })
+        );
+        assert_eq!(
+            Field::parse(FieldKind::DayOfWeek, "1-9", false),
+            Err(FieldError::OutOfRange {
+                span: 2..3,
+                value: 9,
+            })
+        );
+        assert_eq!(
+            Field::parse(FieldKind::Month, "jan-MAR", false)
+                .unwrap()
+                .canonical(),
+            "1-3"
+        );
+        assert!(
+            Field::parse(FieldKind::DayOfWeek, "7", false)
+                .unwrap()
+                .set()
+                .values()
+                .eq([0])
+        );
+    }
+}
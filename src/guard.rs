@@ -0,0 +1,319 @@
+use crate::describe::{describe_fields, join_and};
+use crate::field::{Base, Field, FieldKind, Item, compress, spec_fields};
+use crate::spec::CronSpec;
+
+const DAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const ORDINALS: [&str; 5] = ["first", "second", "third", "fourth", "fifth"];
+
+/// How a line that picks weekdays by their place in the month (`0#1`, `5L`)
+/// or a day counted back from the month's end (`L`, `L-2`) runs under Vixie
+/// cron, which has no such days: on every day the wanted ones can fall on,
+/// behind a shell test that lets only those through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayGuard {
+    /// The line cron runs, with every day the wanted ones can fall on.
+    pub schedule: CronSpec,
+    /// Test to put in front of the command with `&&`, its `%` escaped for a
+    /// crontab.
+    pub check: String,
+    /// What the check costs, for showing next to the entry.
+    pub note: String,
+}
+
+/// The [`DayGuard`] for one cron line, or `None` when the line needs none or
+/// can't be guarded: only lines whose day-of-week names one weekday by its
+/// places in the month, with any day of the month, or whose day-of-month is
+/// one day counted back from the end, with any weekday, qualify.
+///
+/// The check reads the date with `date`, in the line's own zone when it has
+/// one. Last weekdays and month ends ask `date -d` for a later date, which
+/// needs GNU date.
+///
+/// ```
+/// use cronoisseur::{day_guard, parse_expression};
+///
+/// let spec = parse_expression("first sunday of the month at 03:00")?;
+/// let guard = day_guard(&spec).unwrap();
+/// assert_eq!(guard.schedule.as_string(), "0 3 * * 0");
+/// assert_eq!(guard.check, r#"[ "$(date +\%d)" -le 7 ]"#);
+///
+/// let spec = parse_expression("last day of the month at 23:00")?;
+/// let guard = day_guard(&spec).unwrap();
+/// assert_eq!(guard.schedule.as_string(), "0 23 28-31 * *");
+/// assert_eq!(guard.check, r#"[ "$(date -d tomorrow +\%d)" -eq 1 ]"#);
+/// # Ok::<(), cronoisseur::ParseError>(())
+/// ```
+pub fn day_guard(spec: &CronSpec) -> Option<DayGuard> {
+    if spec.is_reboot() || !spec.extra_lines.is_empty() {
+        return None;
+    }
+    let [_, _, day_of_month, _, day_of_week] = spec_fields(spec).ok()?;
+    let date = match &spec.time_zone {
+        Some(zone) => format!("TZ={zone} date"),
+        None => "date".to_string(),
+    };
+    if day_of_week.is_star()
+        && day_of_week.is_full()
+        && let Some(offset) = month_end(&day_of_month)
+    {
+        return Some(month_end_guard(spec, offset, &date));
+    }
+    if !day_of_month.is_star() || !day_of_month.is_full() {
+        return None;
+    }
+    let (weekday, nths, last) = ordinal_weekday(&day_of_week)?;
+
+    let day = format!("\"$({date} +\\%d)\"");
+    let mut tests: Vec<String> = nths
+        .iter()
+        .map(|nth| match nth {
+            1 => format!("[ {day} -le 7 ]"),
+            5 => format!("[ {day} -ge 29 ]"),
+            _ => format!("[ {day} -ge {} ] && [ {day} -le {} ]", nth * 7 - 6, nth * 7),
+        })
+        .collect();
+    if last {
+        tests.push(format!("[ \"$({date} -d '+7 days' +\\%d)\" -le 7 ]"));
+    }
+    let check = match tests.as_slice() {
+        [only] => only.clone(),
+        _ => {
+            let braced: Vec<String> = tests
+                .iter()
+                .map(|test| {
+                    if test.contains("&&") {
+                        format!("{{ {test}; }}")
+                    } else {
+                        test.clone()
+                    }
+                })
+                .collect();
+            format!("{{ {}; }}", braced.join(" || "))
+        }
+    };
+
+    let mut places: Vec<String> = nths
+        .iter()
+        .map(|nth| ORDINALS[*nth as usize - 1].to_string())
+        .collect();
+    if last {
+        places.push("last".to_string());
+    }
+    let name = DAY_NAMES[weekday as usize % 7];
+    let note = format!(
+        "Vixie cron can't pick the {} {name} of the month, so the entry runs every {name} and a date check in the command skips the rest",
+        join_and(&places)
+    );
+    Some(DayGuard {
+        schedule: CronSpec {
+            day_of_week: (weekday % 7).to_string(),
+            ..spec.clone()
+        },
+        check,
+        note,
+    })
+}
+
+/// The guard for a line whose day-of-month is `offset` days before the last.
+fn month_end_guard(spec: &CronSpec, offset: u32, date: &str) -> DayGuard {
+    let days: Vec<u32> = (28u32.saturating_sub(offset).max(1)..=31 - offset).collect();
+    let later = match offset {
+        0 => "tomorrow".to_string(),
+        _ => format!("'+{} days'", offset + 1),
+    };
+    let day = match offset {
+        0 => "the last day of the month".to_string(),
+        1 => "the day before the last day of the month".to_string(),
+        _ => format!("the day {offset} days before the last day of the month"),
+    };
+    let day_of_month = compress(FieldKind::DayOfMonth, &days);
+    DayGuard {
+        check: format!("[ \"$({date} -d {later} +\\%d)\" -eq 1 ]"),
+        note: format!(
+            "Vixie cron can't pick {day}, so the entry runs on days {day_of_month} and a date check in the command skips the rest"
+        ),
+        schedule: CronSpec {
+            day_of_month,
+            ..spec.clone()
+        },
+    }
+}
+
+/// Undo [`day_guard`]: when `command` starts with the check a guard would
+/// put in front of it for some places of `schedule`'s weekday, or some day
+/// before the end of the month, the schedule with those days and the command
+/// without the check. `cron_tz` is the `CRON_TZ` in force, for a schedule
+/// that doesn't carry its zone itself.
+pub(crate) fn strip_day_guard(
+    schedule: &CronSpec,
+    command: &str,
+    cron_tz: Option<&str>,
+) -> Option<(CronSpec, String)> {
+    let [_, _, day_of_month, _, day_of_week] = spec_fields(schedule).ok()?;
+    // Each (day-of-month, day-of-week) a guard could have been made for.
+    let candidates: Vec<(String, String)> = match day_of_week.items.as_slice() {
+        [
+            Item {
+                base: Base::Value(day),
+                step: None,
+            },
+        ] if day_of_month.is_star() && day_of_month.is_full() => {
+            // Five places and `L`: every combination of them.
+            (1u32..1 << 6)
+                .map(|mask| {
+                    let mut items: Vec<String> = (1..=5)
+                        .filter(|nth| mask & (1 << (nth - 1)) != 0)
+                        .map(|nth| format!("{day}#{nth}"))
+                        .collect();
+                    if mask & (1 << 5) != 0 {
+                        items.push(format!("{day}L"));
+                    }
+                    ("*".to_string(), items.join(","))
+                })
+                .collect()
+        }
+        _ if day_of_week.is_star() && day_of_week.is_full() && !day_of_month.is_full() => (0..=30)
+            .map(|offset| match offset {
+                0 => ("L".to_string(), "*".to_string()),
+                _ => (format!("L-{offset}"), "*".to_string()),
+            })
+            .collect(),
+        _ => return None,
+    };
+    candidates.into_iter().find_map(|(dom, dow)| {
+        let plain = CronSpec {
+            day_of_month: dom,
+            day_of_week: dow,
+            time_zone: None,
+            ..schedule.clone()
+        };
+        let mut candidate = CronSpec {
+            explanation: describe_fields(&spec_fields(&plain).ok()?),
+            ..plain
+        };
+        if let Some(zone) = schedule.zone() {
+            candidate = candidate.with_time_zone(zone);
+        }
+        let guard = day_guard(&CronSpec {
+            time_zone: candidate
+                .time_zone
+                .clone()
+                .or_else(|| cron_tz.map(str::to_string)),
+            ..candidate.clone()
+        })?;
+        if guard.schedule.day_of_month != schedule.day_of_month {
+            return None;
+        }
+        let rest = command.strip_prefix(&guard.check)?.strip_prefix(" && ")?;
+        Some((candidate, rest.trim_start().to_string()))
+    })
+}
+
+/// How many days before the last day of the month a day-of-month field
+/// picks, when it is a single `L` or `L-n` and nothing else.
+fn month_end(field: &Field) -> Option<u32> {
+    match field.items.as_slice() {
+        [
+            Item {
+                base: Base::Last(offset),
+                step: None,
+            },
+        ] => Some(*offset),
+        _ => None,
+    }
+}
+
+/// The weekday a day-of-week field picks by place in the month, the places
+/// (`1` to `5`) and whether it also picks the last one. `None` unless every
+/// item is such a day for the same weekday.
+fn ordinal_weekday(field: &Field) -> Option<(u32, Vec<u32>, bool)> {
+    let mut weekday = None;
+    let mut nths = Vec::new();
+    let mut last = false;
+    for item in &field.items {
+        let day = match item.base {
+            Base::Nth(day, nth) => {
+                nths.push(nth);
+                day
+            }
+            Base::LastOf(day) => {
+                last = true;
+                day
+            }
+            _ => return None,
+        };
+        if *weekday.get_or_insert(day % 7) != day % 7 {
+            return None;
+        }
+    }
+    nths.sort_unstable();
+    nths.dedup();
+    Some((weekday?, nths, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn guard(expression: &str) -> DayGuard {
+        day_guard(&parse_expression(expression).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn several_places_are_checked_together() {
+        let guard = guard("second and fourth tuesday of the month at 10:00");
+        assert_eq!(guard.schedule.as_string(), "0 10 * * 2");
+        assert_eq!(
+            guard.check,
+            r#"{ { [ "$(date +\%d)" -ge 8 ] && [ "$(date +\%d)" -le 14 ]; } || { [ "$(date +\%d)" -ge 22 ] && [ "$(date +\%d)" -le 28 ]; }; }"#
+        );
+        assert_eq!(
+            guard.note,
+            "Vixie cron can't pick the second and fourth Tuesday of the month, so the entry runs every Tuesday and a date check in the command skips the rest"
+        );
+    }
+
+    #[test]
+    fn month_ends_look_ahead_to_the_first() {
+        let guard = guard("3 days before the end of the month at 06:00");
+        assert_eq!(guard.schedule.as_string(), "0 6 25-28 * *");
+        assert_eq!(guard.check, r#"[ "$(date -d '+4 days' +\%d)" -eq 1 ]"#);
+    }
+
+    #[test]
+    fn zoned_lines_read_the_date_in_their_zone() {
+        let guard = guard("last friday of the month at 09:00 Europe/Berlin");
+        assert_eq!(
+            guard.check,
+            r#"[ "$(TZ=Europe/Berlin date -d '+7 days' +\%d)" -le 7 ]"#
+        );
+    }
+
+    #[test]
+    fn only_single_weekdays_and_month_ends_are_guarded() {
+        assert!(day_guard(&parse_expression("0 9 * * 1").unwrap()).is_none());
+        assert!(day_guard(&CronSpec::new("0", "9", "*", "*", "1#1,3#2", "")).is_none());
+        assert!(day_guard(&CronSpec::new("0", "9", "L", "*", "1", "")).is_none());
+    }
+
+    #[test]
+    fn guarded_commands_read_back_as_the_day_they_pick() {
+        let spec = parse_expression("first and last monday of the month at 08:00").unwrap();
+        let guard = day_guard(&spec).unwrap();
+        let command = format!("{} && ./report.sh", guard.check);
+        let (schedule, rest) = strip_day_guard(&guard.schedule, &command, None).unwrap();
+        assert_eq!(schedule.as_string(), spec.as_string());
+        assert_eq!(rest, "./report.sh");
+        assert!(strip_day_guard(&guard.schedule, "./report.sh", None).is_none());
+    }
+}
@@ -0,0 +1,284 @@
+use crate::spec::CronEntry;
+use crate::yaml::to_yaml;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// What Kubernetes does when a run is due while the previous one is still going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConcurrencyPolicy {
+    Allow,
+    Forbid,
+    Replace,
+}
+
+/// CronJob settings that have no cron equivalent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CronJobOptions {
+    pub image: String,
+    pub namespace: Option<String>,
+    /// IANA zone the schedule is evaluated in (`spec.timeZone`); defaults to
+    /// the schedule's own.
+    pub time_zone: Option<String>,
+    pub concurrency_policy: Option<ConcurrencyPolicy>,
+    pub successful_jobs_history_limit: Option<u32>,
+    pub failed_jobs_history_limit: Option<u32>,
+}
+
+/// Render `entry` as a `batch/v1` CronJob manifest in YAML.
+///
+/// The job is named after the entry's managed id. The command is split back
+/// into words for the container's `command`, `--env` pairs become container
+/// env vars and the comment is kept as a `description` annotation. A
+/// schedule that takes several cron lines becomes one CronJob per line, the
+/// later ones suffixed `-2`, `-3` and so on.
+///
+/// ```
+/// use cronoisseur::{CronEntry, CronJobOptions, parse_expression, render_cronjob};
+///
+/// let entry = CronEntry {
+///     id: Some("nightly-backup".to_string()),
+///     schedule: parse_expression("daily at 02:00")?,
+///     command: "backup.sh --full".to_string(),
+///     comment: None,
+///     env: Vec::new(),
+/// };
+/// let options = CronJobOptions {
+///     image: "busybox:1.36".to_string(),
+///     ..Default::default()
+/// };
+/// let manifest = render_cronjob(&entry, &options);
+/// assert!(manifest.contains("schedule: \"0 2 * * *\""));
+/// # Ok::<(), cronoisseur::ParseError>(())
+/// ```
+pub fn render_cronjob(entry: &CronEntry, options: &CronJobOptions) -> String {
+    let base = resource_name(&entry.managed_id());
+    let command = shlex::split(&entry.command).unwrap_or_else(|| {
+        vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            entry.command.clone(),
+        ]
+    });
+    let lines = entry.schedule.lines();
+    let documents: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let name = match index {
+                0 => base.clone(),
+                _ => {
+                    let suffix = format!("-{}", index + 1);
+                    let keep = base.len().min(52 - suffix.len());
+                    format!("{}{suffix}", base[..keep].trim_end_matches('-'))
+                }
+            };
+            render_one(entry, options, name, line.as_string(), command.clone())
+        })
+        .collect();
+    documents.join("---\n")
+}
+
+fn render_one(
+    entry: &CronEntry,
+    options: &CronJobOptions,
+    name: String,
+    schedule: String,
+    command: Vec<String>,
+) -> String {
+    let cronjob = CronJob {
+        api_version: "batch/v1",
+        kind: "CronJob",
+        metadata: Metadata {
+            name: name.clone(),
+            namespace: options.namespace.clone(),
+            labels: BTreeMap::from([("app.kubernetes.io/managed-by", "cronoisseur".to_string())]),
+            annotations: entry
+                .comment
+                .iter()
+                .map(|comment| ("description", comment.clone()))
+                .collect(),
+        },
+        spec: CronJobSpec {
+            schedule,
+            time_zone: options
+                .time_zone
+                .clone()
+                .or_else(|| entry.schedule.time_zone.clone()),
+            concurrency_policy: options.concurrency_policy,
+            successful_jobs_history_limit: options.successful_jobs_history_limit,
+            failed_jobs_history_limit: options.failed_jobs_history_limit,
+            job_template: JobTemplate {
+                spec: JobSpec {
+                    template: PodTemplate {
+                        spec: PodSpec {
+                            restart_policy: "OnFailure",
+                            containers: vec![Container {
+                                name,
+                                image: options.image.clone(),
+                                command,
+                                env: entry
+                                    .env
+                                    .iter()
+                                    .map(|env| EnvEntry {
+                                        name: env.key.clone(),
+                                        value: env.value.clone(),
+                                    })
+                                    .collect(),
+                            }],
+                        },
+                    },
+                },
+            },
+        },
+    };
+    to_yaml(&cronjob)
+}
+
+/// A DNS-1123 label: lowercase alphanumerics and `-`, at most 52 characters
+/// so the Job names Kubernetes derives from it stay within limits.
+fn resource_name(id: &str) -> String {
+    let mut name = String::new();
+    for ch in id.chars() {
+        if ch.is_ascii_alphanumeric() {
+            name.push(ch.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    name.truncate(52);
+    let name = name.trim_end_matches('-');
+    if name.is_empty() {
+        "cronjob".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CronJob {
+    api_version: &'static str,
+    kind: &'static str,
+    metadata: Metadata,
+    spec: CronJobSpec,
+}
+
+#[derive(Serialize)]
+struct Metadata {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    labels: BTreeMap<&'static str, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CronJobSpec {
+    schedule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_zone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    concurrency_policy: Option<ConcurrencyPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    successful_jobs_history_limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed_jobs_history_limit: Option<u32>,
+    job_template: JobTemplate,
+}
+
+#[derive(Serialize)]
+struct JobTemplate {
+    spec: JobSpec,
+}
+
+#[derive(Serialize)]
+struct JobSpec {
+    template: PodTemplate,
+}
+
+#[derive(Serialize)]
+struct PodTemplate {
+    spec: PodSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PodSpec {
+    restart_policy: &'static str,
+    containers: Vec<Container>,
+}
+
+#[derive(Serialize)]
+struct Container {
+    name: String,
+    image: String,
+    command: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    env: Vec<EnvEntry>,
+}
+
+#[derive(Serialize)]
+struct EnvEntry {
+    name: String,
+    value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn entry(expression: &str, id: &str) -> CronEntry {
+        CronEntry {
+            id: Some(id.to_string()),
+            schedule: parse_expression(expression).unwrap(),
+            command: "backup.sh --full".to_string(),
+            comment: None,
+            env: Vec::new(),
+        }
+    }
+
+    fn options() -> CronJobOptions {
+        CronJobOptions {
+            image: "busybox:1.36".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn names_are_dns_labels() {
+        assert_eq!(resource_name("Nightly_Backup!!"), "nightly-backup");
+        assert_eq!(resource_name("--"), "cronjob");
+        assert_eq!(resource_name(&"a".repeat(60)).len(), 52);
+    }
+
+    #[test]
+    fn each_line_gets_a_cronjob_of_its_own() {
+        let manifest = render_cronjob(
+            &entry("daily at 09:00 and 17:30 Europe/Berlin", "report"),
+            &options(),
+        );
+        let documents: Vec<&str> = manifest.split("---\n").collect();
+        assert_eq!(documents.len(), 2);
+        assert!(documents[0].contains("name: \"report\"\n"));
+        assert!(documents[0].contains("schedule: \"0 9 * * *\"\n  timeZone: \"Europe/Berlin\"\n"));
+        assert!(documents[1].contains("name: \"report-2\"\n"));
+        assert!(documents[1].contains("schedule: \"30 17 * * *\"\n"));
+    }
+
+    #[test]
+    fn the_command_is_split_into_words() {
+        let manifest = render_cronjob(&entry("daily at 02:00", "backup"), &options());
+        assert!(
+            manifest.contains("command:\n            - \"backup.sh\"\n            - \"--full\"\n")
+        );
+        let zoned = CronJobOptions {
+            time_zone: Some("UTC".to_string()),
+            ..options()
+        };
+        let manifest = render_cronjob(&entry("daily at 02:00 Europe/Berlin", "backup"), &zoned);
+        assert!(manifest.contains("timeZone: \"UTC\"\n"));
+    }
+}
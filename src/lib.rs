@@ -0,0 +1,76 @@
+//! Translate natural-language schedules into cron entries.
+//!
+//! [`parse_expression`] turns phrases such as `weekdays at 07:15` (or a raw
+//! five-field cron expression) into a [`CronSpec`]; [`parse_expression_in`]
+//! also reads Quartz and Spring expressions, and [`CronSpec::to_dialect`]
+//! writes them. Wrap it in a [`CronEntry`]
+//! and hand it to [`render_entry`] to get the lines that belong in a crontab;
+//! [`next_runs`] lists when a schedule will actually fire, and [`explain`]
+//! goes the other way, describing a raw cron expression in English.
+//!
+//! ```
+//! use cronoisseur::{CronEntry, parse_expression, render_entry};
+//!
+//! let entry = CronEntry {
+//!     id: Some("nightly-backup".to_string()),
+//!     schedule: parse_expression("daily at 02:00")?,
+//!     command: "backup.sh".to_string(),
+//!     comment: Some("Nightly backup".to_string()),
+//!     env: Vec::new(),
+//! };
+//! assert_eq!(
+//!     render_entry(&entry),
+//!     "# cronoisseur:id=nightly-backup\n# Nightly backup\n0 2 * * * backup.sh"
+//! );
+//! # Ok::<(), cronoisseur::ParseError>(())
+//! ```
+
+mod cadence;
+mod calendar;
+mod ci;
+mod crontab;
+mod describe;
+mod dialect;
+mod dst;
+mod error;
+mod eventbridge;
+mod field;
+mod guard;
+mod k8s;
+mod next;
+mod parse;
+mod render;
+mod spec;
+mod store;
+mod systemd;
+mod yaml;
+mod zone;
+
+pub use cadence::uneven_intervals;
+pub use calendar::missing_dates;
+pub use ci::{CiPlatform, CiSchedule, render_ci};
+pub use crontab::{
+    Crontab, CrontabJob, UnparsedLine, WriteStatus, find_entry, merge_entry, parse_crontab,
+    remove_entry, replace_entry, unused_id,
+};
+pub use describe::explain;
+pub use dialect::Dialect;
+pub use dst::{DstEffect, DstHazard, dst_hazards};
+pub use error::{ConvertError, Error, ParseError, Result};
+pub use eventbridge::{EventBridgeSchedule, render_eventbridge};
+pub use guard::{DayGuard, day_guard};
+pub use k8s::{ConcurrencyPolicy, CronJobOptions, render_cronjob};
+pub use next::next_runs;
+pub use parse::{PATTERN_GUIDE, parse_expression, parse_expression_in};
+pub use render::{append_entry, default_cron_file, detect_cron_file, render_entry};
+pub use spec::{CronEntry, CronSpec, EnvVar};
+pub use store::{
+    Backend, Backups, edit_crontab, list_backups, read_crontab, restore_backup, write_crontab,
+    write_entry, write_output,
+};
+pub use systemd::{
+    SystemdUnits, TimerOptions, UnitScope, on_calendar, render_systemd, write_systemd,
+};
+pub use zone::{
+    LocalizedEntry, ZoneShift, ZoneStyle, host_zone, localize_entry, shift_to_utc, shift_zone,
+};
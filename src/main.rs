@@ -1,40 +1,36 @@
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Result, anyhow, bail};
 use atty::Stream;
-use clap::Parser;
-use once_cell::sync::Lazy;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use cronoisseur::{
+    Backend, Backups, CiPlatform, ConcurrencyPolicy, CronEntry, CronJobOptions, CronSpec,
+    CrontabJob, Dialect, EnvVar, PATTERN_GUIDE, SystemdUnits, TimerOptions, UnitScope,
+    UnparsedLine, WriteStatus, ZoneStyle, day_guard, detect_cron_file, dst_hazards, edit_crontab,
+    find_entry, host_zone, list_backups, localize_entry, missing_dates, next_runs, parse_crontab,
+    parse_expression_in, remove_entry, render_ci, render_cronjob, render_entry, render_eventbridge,
+    render_systemd, replace_entry, restore_backup, uneven_intervals, unused_id, write_entry,
+    write_output, write_systemd,
+};
 use owo_colors::OwoColorize;
-use regex::Regex;
 use serde::Serialize;
 use shlex::try_quote;
 use std::env;
-use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
-const PATTERN_GUIDE: &[(&str, &str)] = &[
-    ("daily at HH:MM", "daily at 05:30"),
-    ("weekdays at HH:MM", "weekdays at 07:15"),
-    ("weekends at HH:MM", "weekends at 19:05"),
-    ("<days> at HH:MM", "monday wednesday at 03:00"),
-    ("weekly on <days> at HH:MM", "weekly on fri at 02:45"),
-    (
-        "monthly on <dates> at HH:MM",
-        "monthly on 1st and 15th at 04:00",
-    ),
-    ("on <dates> at HH:MM", "on 10,20 at 22:30"),
-    ("every N minutes", "every 15 minutes"),
-    ("every N hours", "every 2 hours"),
-    ("hourly at :MM", "hourly at :10"),
-    ("raw cron", "30 3 * * 1"),
-];
-
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
-    about = "Translate natural language schedules into cron entries."
+    about = "Translate natural language schedules into cron entries.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Cli {
+    #[command(subcommand)]
+    mode: Option<Mode>,
+
     /// Natural language schedule or raw cron expression
     #[arg(value_name = "expression", required_unless_present = "list_patterns")]
     expression: Option<String>,
@@ -43,11 +39,15 @@ struct Cli {
     #[arg(short, long, value_name = "text")]
     comment: Option<String>,
 
-    /// Optional cron file to append the entry to
+    /// Identifier for the entry's `# cronoisseur:id=` marker (defaults to a slug of the comment or command)
+    #[arg(long, value_name = "id", value_parser = parse_id)]
+    id: Option<String>,
+
+    /// Cron file to write the entry to (implies --backend file)
     #[arg(short, long, value_name = "file", requires = "write")]
     file: Option<PathBuf>,
 
-    /// Write the entry to the user's cron file (auto-detected unless --file is provided)
+    /// Write the entry to the user's crontab
     #[arg(long)]
     write: bool,
 
@@ -67,6 +67,18 @@ struct Cli {
     #[arg(long)]
     list_patterns: bool,
 
+    /// List the next N times the schedule fires
+    #[arg(long, value_name = "N")]
+    next: Option<usize>,
+
+    /// Starting point for --next (RFC 3339 or `YYYY-MM-DD[ HH:MM]`, local time); defaults to now
+    #[arg(long, value_name = "timestamp", requires = "next", value_parser = parse_timestamp)]
+    from: Option<DateTime<Local>>,
+
+    /// Append the entry even if an identical one is already in the file; a derived id that is taken gets a `-2`, `-3`, ... suffix
+    #[arg(long, requires = "write")]
+    allow_duplicate: bool,
+
     /// Environment key=val pairs to set before the entry
     #[arg(
         long = "env",
@@ -76,6 +88,82 @@ struct Cli {
     )]
     env: Vec<EnvVar>,
 
+    /// How crontabs are read and written (defaults to `crontab` when it is on PATH)
+    #[arg(long, value_enum, value_name = "backend", global = true)]
+    backend: Option<BackendKind>,
+
+    /// Whose crontab to change with the crontab backend (`crontab -u`)
+    #[arg(short, long, value_name = "user", global = true)]
+    user: Option<String>,
+
+    /// What to generate: a crontab entry, a systemd timer and service, a Kubernetes CronJob, a CI schedule or an EventBridge expression
+    #[arg(long, value_enum, value_name = "target", default_value_t = Target::Cron)]
+    target: Target,
+
+    /// Container image to run (k8s)
+    #[arg(long, value_name = "image")]
+    image: Option<String>,
+
+    /// Namespace for the CronJob (k8s)
+    #[arg(long, value_name = "namespace")]
+    namespace: Option<String>,
+
+    /// IANA time zone the schedule is meant in, e.g. `Europe/Berlin` (same as ending the phrase with it)
+    #[arg(long, value_name = "zone", value_parser = parse_time_zone)]
+    tz: Option<Tz>,
+
+    /// How a crontab entry carries its time zone: a CRON_TZ= line, a TZ= line, or fields shifted to --host-tz
+    #[arg(long, value_enum, value_name = "style", default_value_t = TzStyle::CronTz)]
+    tz_style: TzStyle,
+
+    /// Zone the cron daemon runs in, for --tz-style shift and daylight saving checks (defaults to this machine's)
+    #[arg(long, value_name = "zone", value_parser = parse_time_zone)]
+    host_tz: Option<Tz>,
+
+    /// What to do when a run is due while the previous one is still going (k8s)
+    #[arg(long, value_enum, value_name = "policy")]
+    concurrency_policy: Option<Concurrency>,
+
+    /// Finished jobs to keep (k8s)
+    #[arg(long, value_name = "N")]
+    successful_jobs_history_limit: Option<u32>,
+
+    /// Failed jobs to keep (k8s)
+    #[arg(long, value_name = "N")]
+    failed_jobs_history_limit: Option<u32>,
+
+    /// Run a missed activation once the machine is back up (systemd `Persistent=`)
+    #[arg(long)]
+    persistent: bool,
+
+    /// Delay each activation by a random amount up to this span, e.g. `5m` (systemd `RandomizedDelaySec=`)
+    #[arg(long, value_name = "span", value_parser = parse_time_span)]
+    randomized_delay: Option<String>,
+
+    /// Install systemd units for the current user or system-wide
+    #[arg(long, value_enum, value_name = "scope", default_value_t = Scope::User)]
+    scope: Scope,
+
+    /// Directory to write systemd units to (overrides --scope)
+    #[arg(long, value_name = "dir")]
+    unit_dir: Option<PathBuf>,
+
+    /// Cron syntax raw expressions are written in, and that schedules are shown in
+    #[arg(long, value_enum, value_name = "dialect", default_value_t = Syntax::Vixie, global = true)]
+    dialect: Syntax,
+
+    /// Show schedules in another dialect than --dialect, e.g. to convert Quartz to cron
+    #[arg(long, value_enum, value_name = "dialect", global = true)]
+    to_dialect: Option<Syntax>,
+
+    /// Directory for the backups taken before a crontab is changed
+    #[arg(long, value_name = "dir", global = true)]
+    backup_dir: Option<PathBuf>,
+
+    /// Number of backups to keep per crontab (0 disables backups)
+    #[arg(long, value_name = "N", default_value_t = 10, global = true)]
+    backup_keep: usize,
+
     /// Command to schedule
     #[arg(
         value_name = "command",
@@ -86,64 +174,261 @@ struct Cli {
     command: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct EnvVar {
-    key: String,
-    value: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct CronSpec {
-    minute: String,
-    hour: String,
-    day_of_month: String,
-    month: String,
-    day_of_week: String,
-    explanation: String,
-}
-
-impl CronSpec {
-    fn new(
-        minute: impl Into<String>,
-        hour: impl Into<String>,
-        day_of_month: impl Into<String>,
-        month: impl Into<String>,
-        day_of_week: impl Into<String>,
-        explanation: impl Into<String>,
-    ) -> Self {
-        Self {
-            minute: minute.into(),
-            hour: hour.into(),
-            day_of_month: day_of_month.into(),
-            month: month.into(),
-            day_of_week: day_of_week.into(),
-            explanation: explanation.into(),
+#[derive(Subcommand, Debug)]
+enum Mode {
+    /// Describe a cron expression (or phrase) in English
+    Explain {
+        /// Raw cron expression or natural language schedule
+        #[arg(value_name = "expression")]
+        expression: String,
+
+        /// Emit JSON describing the schedule
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rewrite a managed entry in place
+    Update {
+        /// Identifier from the entry's `# cronoisseur:id=` marker
+        #[arg(value_name = "id")]
+        id: String,
+
+        /// New natural language schedule or raw cron expression
+        #[arg(value_name = "expression")]
+        expression: String,
+
+        /// New comment (keeps the current one when omitted)
+        #[arg(short, long, value_name = "text")]
+        comment: Option<String>,
+
+        /// New environment key=val pairs (keeps the current ones when omitted)
+        #[arg(long = "env", value_name = "key=value", num_args = 0.., value_parser = parse_env_var)]
+        env: Vec<EnvVar>,
+
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// New command (keeps the current one when omitted)
+        #[arg(value_name = "command", num_args = 0.., trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+    /// List and explain every job in a crontab
+    List {
+        /// Cron file to read (implies --backend file)
+        #[arg(short, long, value_name = "file")]
+        file: Option<PathBuf>,
+
+        /// Treat the file as a system crontab with a user column (implied for /etc/crontab and /etc/cron.d)
+        #[arg(long)]
+        system: bool,
+
+        /// Emit JSON describing every job
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a managed entry
+    Remove {
+        /// Identifier from the entry's `# cronoisseur:id=` marker
+        #[arg(value_name = "id")]
+        id: String,
+
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+    /// Roll a crontab back to the backup taken before its last change
+    Restore {
+        /// Cron file to restore (implies --backend file)
+        #[arg(short, long, value_name = "file")]
+        file: Option<PathBuf>,
+
+        /// Emit JSON describing the restore
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    /// A crontab entry
+    Cron,
+    /// A systemd `.timer` and `.service` pair
+    Systemd,
+    /// A Kubernetes `batch/v1` CronJob manifest
+    K8s,
+    /// An `on: schedule` block for a GitHub Actions workflow
+    GithubActions,
+    /// A GitLab pipeline schedule and the job it runs
+    Gitlab,
+    /// An AWS EventBridge `rate()` or `cron()` schedule expression
+    Eventbridge,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Concurrency {
+    /// Let runs overlap
+    Allow,
+    /// Skip a run while the previous one is still going
+    Forbid,
+    /// Cancel the running job and start the new one
+    Replace,
+}
+
+impl From<Concurrency> for ConcurrencyPolicy {
+    fn from(policy: Concurrency) -> Self {
+        match policy {
+            Concurrency::Allow => ConcurrencyPolicy::Allow,
+            Concurrency::Forbid => ConcurrencyPolicy::Forbid,
+            Concurrency::Replace => ConcurrencyPolicy::Replace,
         }
     }
+}
 
-    fn as_string(&self) -> String {
-        format!(
-            "{} {} {} {} {}",
-            self.minute, self.hour, self.day_of_month, self.month, self.day_of_week
-        )
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Scope {
+    /// `~/.config/systemd/user`, managed with `systemctl --user`
+    User,
+    /// `/etc/systemd/system`
+    System,
+}
+
+impl From<Scope> for UnitScope {
+    fn from(scope: Scope) -> Self {
+        match scope {
+            Scope::User => UnitScope::User,
+            Scope::System => UnitScope::System,
+        }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct CronEntry {
-    schedule: CronSpec,
-    command: String,
-    comment: Option<String>,
-    env: Vec<EnvVar>,
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum TzStyle {
+    /// `CRON_TZ=<zone>` above the job, as cronie reads it
+    CronTz,
+    /// `TZ=<zone>` above the job
+    Tz,
+    /// Rewrite the hours and days into the host's zone
+    Shift,
+}
+
+impl From<TzStyle> for ZoneStyle {
+    fn from(style: TzStyle) -> Self {
+        match style {
+            TzStyle::CronTz => ZoneStyle::CronTz,
+            TzStyle::Tz => ZoneStyle::Tz,
+            TzStyle::Shift => ZoneStyle::Shift,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Syntax {
+    /// Five fields: `15 7 * * 1-5`
+    Vixie,
+    /// Seconds first, `?` in one day field, optional year: `0 15 7 ? * MON-FRI`
+    Quartz,
+    /// Seconds first, Vixie day numbers: `0 15 7 * * MON-FRI`
+    Spring,
+}
+
+impl From<Syntax> for Dialect {
+    fn from(syntax: Syntax) -> Self {
+        match syntax {
+            Syntax::Vixie => Dialect::Vixie,
+            Syntax::Quartz => Dialect::Quartz,
+            Syntax::Spring => Dialect::Spring,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum BackendKind {
+    /// Install through the system `crontab` binary
+    Crontab,
+    /// Edit a cron file directly (see --file)
+    File,
+}
+
+#[derive(Args, Debug)]
+struct TargetArgs {
+    /// Cron file holding the entry (implies --backend file)
+    #[arg(short, long, value_name = "file")]
+    file: Option<PathBuf>,
+
+    /// Preview without writing
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Emit JSON describing the change
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ListReport<'a> {
+    file: String,
+    jobs: Vec<ListedJob<'a>>,
+    unparsed: &'a [UnparsedLine],
+}
+
+#[derive(Debug, Serialize)]
+struct ListedJob<'a> {
+    #[serde(flatten)]
+    job: &'a CrontabJob,
+    next_run: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChangeReport {
+    action: &'static str,
+    id: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<CronEntry>,
+    wrote_file: bool,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainReport<'a> {
+    cron: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guard: Option<String>,
+    #[serde(flatten)]
+    schedule: &'a CronSpec,
+}
+
+#[derive(Debug, Serialize)]
+struct RestoreReport {
+    file: String,
+    backup: PathBuf,
+    remaining: usize,
 }
 
 #[derive(Debug, Serialize)]
 struct JsonReport {
     cron: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guard: Option<String>,
     entry: CronEntry,
-    file: Option<PathBuf>,
+    file: Option<String>,
     wrote_file: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<WriteStatus>,
     dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_runs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    units: Option<SystemdUnits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifest: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+/// The entry rendered for the chosen `--target`.
+enum Rendered {
+    Entry(String),
+    Units(SystemdUnits),
+    Manifest(String),
 }
 
 struct Painter {
@@ -204,55 +489,208 @@ fn run() -> Result<()> {
         return Ok(());
     }
 
+    let backups = Backups {
+        dir: cli.backup_dir.clone().unwrap_or_else(Backups::default_dir),
+        keep: cli.backup_keep,
+    };
+
+    let dialect = Dialect::from(cli.dialect);
+    let shown = Dialect::from(cli.to_dialect.unwrap_or(cli.dialect));
+
+    match &cli.mode {
+        Some(Mode::Explain { expression, json }) => {
+            return run_explain(&painter, expression, dialect, shown, *json);
+        }
+        Some(Mode::List { file, system, json }) => {
+            let backend = select_backend(&cli, file.as_deref())?;
+            return run_list(&painter, &backend, *system, *json);
+        }
+        Some(mode @ (Mode::Update { target, .. } | Mode::Remove { target, .. })) => {
+            let backend = select_backend(&cli, target.file.as_deref())?;
+            return run_change(&painter, mode, dialect, &backend, &backups);
+        }
+        Some(Mode::Restore { file, json }) => {
+            let backend = select_backend(&cli, file.as_deref())?;
+            return run_restore(&painter, &backend, *json, &backups);
+        }
+        None => {}
+    }
+
     let expression = cli
         .expression
         .as_deref()
         .expect("expression is required unless --list-patterns is used");
-    let schedule = parse_expression(expression)
-        .with_context(|| format!("Could not parse expression `{expression}`"))?;
-
-    let command = cli
-        .command
-        .iter()
-        .map(|part| {
-            try_quote(part)
-                .map(|quoted| quoted.to_string())
-                .map_err(|err| anyhow!("Invalid command segment `{part}`: {err}"))
-        })
-        .collect::<Result<Vec<_>>>()?
-        .join(" ");
+    let mut schedule = parse_schedule(expression, dialect)?;
+    if let Some(zone) = cli.tz {
+        match schedule.zone() {
+            Some(named) if named != zone => {
+                bail!("The schedule is meant in {named}, but --tz says {zone}")
+            }
+            Some(_) => {}
+            None => schedule = schedule.with_time_zone(zone),
+        }
+    }
+    let command = quote_command(&cli.command)?;
 
-    let entry = CronEntry {
+    let mut entry = CronEntry {
+        id: cli.id.clone(),
         schedule,
         command,
         comment: cli.comment.clone(),
         env: cli.env.clone(),
     };
+    // Pick the suffixed id up front so the preview shows the one written.
+    if cli.allow_duplicate && cli.target == Target::Cron && entry.id.is_none() {
+        let existing = select_backend(&cli, cli.file.as_deref())?.read()?;
+        entry.id = Some(unused_id(&existing, &entry.managed_id()));
+    }
 
-    let cron_line = entry.schedule.as_string();
-    let preview_block = render_entry(&entry);
+    let from = cli.from.unwrap_or_else(Local::now);
+    let upcoming = match cli.next {
+        Some(count) => next_runs(&entry.schedule, &from, count)?,
+        None => Vec::new(),
+    };
+
+    // The zone whose clock changes decide when the job really runs.
+    let host = cli.host_tz.unwrap_or_else(host_zone);
+    let clock_zone = match cli.target {
+        Target::Cron if cli.tz_style == TzStyle::Shift => Some(host),
+        Target::Cron | Target::Systemd => Some(entry.schedule.zone().unwrap_or(host)),
+        Target::K8s | Target::Gitlab => entry.schedule.zone(),
+        Target::GithubActions | Target::Eventbridge => None,
+    };
+    let mut warnings = entry.schedule.warnings.clone();
+    warnings.extend(uneven_intervals(&entry.schedule));
+    warnings.extend(missing_dates(&entry.schedule));
+    if cli.target == Target::Cron {
+        let local = localize_entry(&entry, cli.tz_style.into(), host, Utc::now())?;
+        warnings.extend(local.warnings);
+        entry = local.entry;
+    }
+    if let Some(zone) = clock_zone {
+        let hazards = dst_hazards(&entry.schedule, zone, from.with_timezone(&Utc), 366)?;
+        warnings.extend(hazards.iter().map(ToString::to_string));
+    }
+
+    let (cron_line, guard) = show_schedule(&entry.schedule, shown, cli.target == Target::Cron)?;
+    match cli.target {
+        Target::Cron => {
+            check_crontab(&entry.schedule)?;
+            for guard in entry.schedule.lines().iter().filter_map(day_guard) {
+                if !warnings.contains(&guard.note) {
+                    warnings.push(guard.note);
+                }
+            }
+        }
+        Target::K8s => {
+            entry.schedule.to_dialect(Dialect::Vixie)?;
+        }
+        _ => {}
+    }
+    if cli.target == Target::K8s && entry.schedule.is_reboot() {
+        bail!("Kubernetes CronJobs only run on a calendar, so @reboot has no equivalent");
+    }
+    let rendered = match cli.target {
+        Target::Cron => Rendered::Entry(render_entry(&entry)),
+        Target::Systemd => Rendered::Units(render_systemd(
+            &entry,
+            &TimerOptions {
+                persistent: cli.persistent,
+                randomized_delay: cli.randomized_delay.clone(),
+            },
+        )?),
+        Target::K8s => Rendered::Manifest(render_cronjob(&entry, &cronjob_options(&cli)?)),
+        Target::GithubActions | Target::Gitlab => {
+            let platform = if cli.target == Target::Gitlab {
+                CiPlatform::Gitlab
+            } else {
+                CiPlatform::GithubActions
+            };
+            let schedule = render_ci(&entry, platform, cli.tz, Utc::now())?;
+            warnings.extend(schedule.warnings);
+            Rendered::Manifest(schedule.snippet)
+        }
+        Target::Eventbridge => {
+            let schedule = render_eventbridge(&entry.schedule)?;
+            warnings.extend(schedule.warnings);
+            Rendered::Manifest(schedule.expression + "\n")
+        }
+    };
+    let preview_block = match &rendered {
+        Rendered::Entry(block) => block.clone(),
+        Rendered::Units(units) => format!(
+            "# {}\n{}\n# {}\n{}",
+            units.timer_file(),
+            units.timer,
+            units.service_file(),
+            units.service.trim_end()
+        ),
+        Rendered::Manifest(manifest) => manifest.trim_end().to_string(),
+    };
 
-    let mut wrote_file = false;
-    let mut target_file = None;
+    let mut status = None;
+    let mut target = None;
     if cli.write {
-        let path = cli
-            .file
-            .clone()
-            .unwrap_or_else(detect_cron_file);
-        target_file = Some(path.clone());
-        if !cli.dry_run {
-            append_entry(&path, &preview_block)?;
-            wrote_file = true;
+        match &rendered {
+            Rendered::Entry(_) => {
+                let backend = select_backend(&cli, cli.file.as_deref())?;
+                if !cli.dry_run {
+                    status = Some(write_entry(
+                        &backend,
+                        &entry,
+                        cli.allow_duplicate,
+                        &backups,
+                    )?);
+                }
+                target = Some(backend.to_string());
+            }
+            Rendered::Units(units) => {
+                if cli.file.is_some() {
+                    bail!("--file only applies to the cron target; use --unit-dir for systemd");
+                }
+                let dir = cli
+                    .unit_dir
+                    .clone()
+                    .unwrap_or_else(|| UnitScope::from(cli.scope).unit_dir());
+                if !cli.dry_run {
+                    status = Some(write_systemd(&dir, units)?);
+                }
+                target = Some(dir.display().to_string());
+            }
+            Rendered::Manifest(manifest) => {
+                let Some(path) = &cli.file else {
+                    bail!("Writing a manifest needs --file <path>");
+                };
+                if !cli.dry_run {
+                    status = Some(write_output(path, manifest)?);
+                }
+                target = Some(path.display().to_string());
+            }
         }
     }
+    let wrote_file = matches!(status, Some(WriteStatus::New | WriteStatus::Replaced));
 
     if cli.json {
         let report = JsonReport {
             cron: cron_line,
+            guard,
             entry,
-            file: target_file.clone(),
+            file: target.clone(),
             wrote_file,
+            status,
             dry_run: cli.dry_run,
+            next_runs: cli
+                .next
+                .map(|_| upcoming.iter().map(DateTime::to_rfc3339).collect()),
+            units: match &rendered {
+                Rendered::Units(units) => Some(units.clone()),
+                _ => None,
+            },
+            manifest: match &rendered {
+                Rendered::Manifest(manifest) => Some(manifest.clone()),
+                _ => None,
+            },
+            warnings,
         };
         println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
@@ -261,624 +699,543 @@ fn run() -> Result<()> {
     print_summary(
         &painter,
         &entry,
-        &preview_block,
         &cron_line,
+        guard.as_deref(),
+        &preview_block,
         &cli,
-        wrote_file,
-        target_file.as_ref(),
+        target.as_deref().map(|target| (target, status)),
+        &upcoming,
     );
-
-    Ok(())
-}
-
-fn print_pattern_guide(painter: &Painter) {
-    println!("{}", painter.accent("Supported phrasing samples:"));
-    for (syntax, example) in PATTERN_GUIDE {
+    if !warnings.is_empty() {
+        println!();
+        for warning in &warnings {
+            println!("{} {warning}", painter.warn("Warning:"));
+        }
+    }
+    if let Rendered::Units(units) = &rendered
+        && cli.write
+    {
+        let systemctl = match cli.scope {
+            Scope::User => "systemctl --user",
+            Scope::System => "systemctl",
+        };
+        println!();
         println!(
-            "  - {:<28} {}",
-            syntax,
-            painter.success(format!("e.g. {}", example))
+            "Activate with: {systemctl} daemon-reload && {systemctl} enable --now {}",
+            units.timer_file()
         );
     }
+
+    Ok(())
 }
 
-fn print_summary(
-    painter: &Painter,
-    entry: &CronEntry,
-    preview: &str,
-    cron_line: &str,
-    cli: &Cli,
-    wrote_file: bool,
-    target_file: Option<&PathBuf>,
-) {
-    println!("{}", painter.accent("Parsed Input"));
-    println!(
-        "Schedule: {}  ({})",
-        painter.success(cron_line),
-        entry.schedule.explanation
-    );
-    println!("Command: {}", entry.command);
-    if let Some(comment) = &entry.comment {
-        println!("Comment: {}", comment);
+/// The schedule in `dialect`, and with `guarded` (output for a crontab) the
+/// shell check that goes in front of the command for lines that need a
+/// [`day_guard`] in Vixie syntax. Guarded lines show the days the guard
+/// narrows down, such as `28-31`; without `guarded`, lines Vixie can't
+/// express are shown as written.
+fn show_schedule(
+    schedule: &CronSpec,
+    dialect: Dialect,
+    guarded: bool,
+) -> Result<(String, Option<String>)> {
+    if dialect != Dialect::Vixie {
+        return Ok((schedule.to_dialect(dialect)?, None));
     }
-    if !entry.env.is_empty() {
-        let env_preview = entry
-            .env
+    if !guarded {
+        let lines: Vec<String> = schedule
+            .lines()
             .iter()
-            .map(|pair| format!("{}={}", pair.key, pair.value))
-            .collect::<Vec<_>>()
-            .join(", ");
-        println!("  Env      : {}", env_preview);
-    }
-    if let Some(path) = target_file {
-        let status = if cli.dry_run {
-            painter.warn("dry run - not written")
-        } else if wrote_file {
-            painter.success("written")
-        } else {
-            painter.warn("skipped")
-        };
-        println!("  File     : {} ({})", path.display(), status);
+            .map(|line| {
+                line.to_dialect(Dialect::Vixie)
+                    .unwrap_or_else(|_| line.as_string())
+            })
+            .collect();
+        return Ok((lines.join("\n"), None));
     }
-    println!("");
-    println!("{}", painter.accent("Preview Output"));
-    println!("{preview}");
+    let mut checks: Vec<String> = Vec::new();
+    let lines = schedule
+        .lines()
+        .iter()
+        .map(|line| match day_guard(line) {
+            Some(guard) => {
+                if !checks.contains(&guard.check) {
+                    checks.push(guard.check);
+                }
+                Ok(guard.schedule.as_string())
+            }
+            None => line.to_dialect(Dialect::Vixie),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let guard = (!checks.is_empty()).then(|| checks.join("\n"));
+    Ok((lines.join("\n"), guard))
 }
 
-fn detect_cron_file() -> PathBuf {
-    if let Ok(path) = env::var("CRONTAB") {
-        return PathBuf::from(path);
+/// Fail unless every line of `schedule` can go into a crontab, as it is or
+/// behind a [`day_guard`].
+fn check_crontab(schedule: &CronSpec) -> Result<()> {
+    for line in schedule.lines() {
+        if day_guard(&line).is_none() {
+            line.to_dialect(Dialect::Vixie)?;
+        }
     }
+    Ok(())
+}
 
-    let username = env::var("USER")
-        .or_else(|_| env::var("USERNAME"))
-        .unwrap_or_else(|_| "user".to_string());
-
-    let candidates = [
-        format!("/var/spool/cron/crontabs/{username}"),
-        format!("/var/spool/cron/{username}"),
-        format!("/etc/cron.d/{username}"),
-    ];
-
-    for candidate in candidates {
-        let path = PathBuf::from(&candidate);
-        if path.exists() || path.parent().is_some_and(Path::exists) {
-            return path;
+fn run_explain(
+    painter: &Painter,
+    expression: &str,
+    dialect: Dialect,
+    shown: Dialect,
+    json: bool,
+) -> Result<()> {
+    let schedule = parse_schedule(expression, dialect)?;
+    let (cron, guard) = show_schedule(&schedule, shown, true)?;
+    if json {
+        let report = ExplainReport {
+            cron,
+            guard,
+            schedule: &schedule,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}  {}", painter.success(cron), schedule.explanation);
+        if let Some(guard) = guard {
+            println!("Guard: {guard}");
         }
     }
-
-    default_cron_file()
+    Ok(())
 }
 
-fn default_cron_file() -> PathBuf {
-    let home = env::var("HOME")
-        .or_else(|_| env::var("USERPROFILE"))
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("."));
-    home.join(".crontab")
-}
+fn run_list(painter: &Painter, backend: &Backend, system: bool, json: bool) -> Result<()> {
+    let system = system
+        || matches!(backend, Backend::File(path) if path == Path::new("/etc/crontab")
+            || path
+                .parent()
+                .is_some_and(|parent| parent.file_name().is_some_and(|name| name == "cron.d")));
+    let crontab = parse_crontab(&backend.read()?, system);
+    let now = Local::now();
+    let next_run = |job: &CrontabJob| {
+        next_runs(&job.entry.schedule, &now, 1)
+            .ok()
+            .and_then(|runs| runs.into_iter().next())
+    };
 
-fn render_entry(entry: &CronEntry) -> String {
-    let mut lines = Vec::new();
-    if let Some(comment) = &entry.comment {
-        lines.push(format!("# {comment}"));
-    }
-    for env in &entry.env {
-        lines.push(format!("{}={}", env.key, env.value));
+    if json {
+        let report = ListReport {
+            file: backend.to_string(),
+            jobs: crontab
+                .jobs
+                .iter()
+                .map(|job| ListedJob {
+                    job,
+                    next_run: next_run(job).map(|run| run.to_rfc3339()),
+                })
+                .collect(),
+            unparsed: &crontab.unparsed,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
     }
-    lines.push(format!("{} {}", entry.schedule.as_string(), entry.command));
-    lines.join("\n")
-}
 
-fn append_entry(path: &Path, block: &str) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed creating {}", parent.display()))?;
+    println!(
+        "{}",
+        painter.accent(format!("{} job(s) in {backend}", crontab.jobs.len()))
+    );
+    for job in &crontab.jobs {
+        let entry = &job.entry;
+        println!(
+            "  line {}: {}  ({})",
+            job.line,
+            painter.success(entry.schedule.as_string()),
+            entry.schedule.explanation
+        );
+        print_job_details(
+            &entry.command,
+            job.user.as_deref(),
+            entry.comment.as_deref(),
+            &entry.env,
+        );
+        match next_run(job) {
+            Some(run) => println!("    Next run: {}", run.format("%Y-%m-%d %H:%M %a")),
+            None if entry.schedule.is_reboot() => println!("    Next run: at system startup"),
+            None => println!("    Next run: {}", painter.warn("none found")),
         }
     }
-
-    let mut payload = String::new();
-    if path.exists() {
-        let metadata = fs::metadata(path)
-            .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
-        if metadata.len() > 0 && !file_ends_with_newline(path)? {
-            payload.push('\n');
+    if !crontab.unparsed.is_empty() {
+        println!();
+        println!("{}", painter.warn("Unparsed lines"));
+        for line in &crontab.unparsed {
+            println!(
+                "  line {}: {}  ({})",
+                line.line,
+                line.text.trim(),
+                line.reason
+            );
         }
     }
-
-    payload.push_str(block);
-    payload.push('\n');
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .with_context(|| format!("Failed opening {}", path.display()))?;
-    file.write_all(payload.as_bytes())
-        .with_context(|| format!("Failed writing to {}", path.display()))?;
     Ok(())
 }
 
-fn file_ends_with_newline(path: &Path) -> Result<bool> {
-    let metadata = fs::metadata(path)
-        .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
-    if metadata.len() == 0 {
-        return Ok(true);
+fn print_job_details(command: &str, user: Option<&str>, comment: Option<&str>, env: &[EnvVar]) {
+    println!("    Command : {command}");
+    if let Some(user) = user {
+        println!("    User    : {user}");
     }
-
-    let mut file = File::open(path)
-        .with_context(|| format!("Failed to open {} for newline inspection", path.display()))?;
-    file.seek(SeekFrom::End(-1))
-        .with_context(|| format!("Failed seeking within {}", path.display()))?;
-    let mut buf = [0u8; 1];
-    file.read_exact(&mut buf)
-        .with_context(|| format!("Failed reading tail byte of {}", path.display()))?;
-    Ok(buf[0] == b'\n')
-}
-
-fn parse_expression(expression: &str) -> Result<CronSpec> {
-    let trimmed = expression.trim();
-    if trimmed.is_empty() {
-        bail!("The expression is empty");
+    if let Some(comment) = comment {
+        println!("    Comment : {comment}");
     }
-
-    if let Some(spec) = try_parse_raw(trimmed) {
-        return Ok(spec);
+    if !env.is_empty() {
+        println!("    Env     : {}", format_env(env));
     }
+}
 
-    let normalized = trimmed.split_whitespace().collect::<Vec<_>>().join(" ");
-    let normalized = normalized
-        .to_lowercase()
-        .replace('–', "-")
-        .replace('—', "-");
+fn format_env(env: &[EnvVar]) -> String {
+    env.iter()
+        .map(|pair| format!("{}={}", pair.key, pair.value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    if let Some(spec) = try_parse_every_minutes(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_hourly(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_every_hours(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_daily(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_weekdayish(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_specific_days(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_monthly(&normalized) {
-        return Ok(spec);
-    }
-    if let Some(spec) = try_parse_on_days(&normalized) {
-        return Ok(spec);
-    }
+/// Handle `update` and `remove`: rewrite one managed block and nothing else.
+fn run_change(
+    painter: &Painter,
+    mode: &Mode,
+    dialect: Dialect,
+    backend: &Backend,
+    backups: &Backups,
+) -> Result<()> {
+    let (id, target) = match mode {
+        Mode::Update { id, target, .. } | Mode::Remove { id, target } => (id, target),
+        Mode::Explain { .. } | Mode::List { .. } | Mode::Restore { .. } => {
+            unreachable!("only update and remove change a crontab")
+        }
+    };
+    let plan = |existing: &str| -> Result<(&'static str, Option<CronEntry>, String)> {
+        let Some(current) = find_entry(existing, id) else {
+            bail!("No entry with id `{id}` in {backend}");
+        };
+        Ok(match mode {
+            Mode::Update {
+                expression,
+                comment,
+                env,
+                command,
+                ..
+            } => {
+                let mut schedule = parse_schedule(expression, dialect)?;
+                check_crontab(&schedule)?;
+                // A new schedule without a zone keeps the one the entry had.
+                if let (None, Some(zone)) = (schedule.zone(), current.schedule.zone()) {
+                    schedule = schedule.with_time_zone(zone);
+                }
+                let entry = CronEntry {
+                    id: Some(id.clone()),
+                    schedule,
+                    command: if command.is_empty() {
+                        current.command
+                    } else {
+                        quote_command(command)?
+                    },
+                    comment: comment.clone().or(current.comment),
+                    env: if env.is_empty() {
+                        current.env
+                    } else {
+                        env.clone()
+                    },
+                };
+                let contents = replace_entry(existing, &entry)
+                    .expect("the entry was found above, so it can be replaced");
+                ("updated", Some(entry), contents)
+            }
+            _ => {
+                let contents = remove_entry(existing, id)
+                    .expect("the entry was found above, so it can be removed");
+                ("removed", None, contents)
+            }
+        })
+    };
 
-    bail!("Unsupported phrasing. Use flag --list-patterns to list all supported shapes.")
-}
+    let (action, entry) = if target.dry_run {
+        let (action, entry, _) = plan(&backend.read()?)?;
+        (action, entry)
+    } else {
+        edit_crontab(backend, backups, |existing| -> Result<_> {
+            let (action, entry, contents) = plan(existing)?;
+            Ok((Some(contents), (action, entry)))
+        })?
+    };
 
-fn try_parse_raw(input: &str) -> Option<CronSpec> {
-    let parts: Vec<_> = input.split_whitespace().collect();
-    if parts.len() != 5 {
-        return None;
+    if target.json {
+        let report = ChangeReport {
+            action,
+            id: id.clone(),
+            file: backend.to_string(),
+            entry,
+            wrote_file: !target.dry_run,
+            dry_run: target.dry_run,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
     }
 
-    if parts.iter().all(|segment| {
-        segment
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || "*?/,-".contains(c))
-    }) {
-        Some(CronSpec::new(
-            parts[0],
-            parts[1],
-            parts[2],
-            parts[3],
-            parts[4],
-            "Raw cron expression".to_string(),
-        ))
+    let status = if target.dry_run {
+        painter.warn("dry run - not written")
     } else {
-        None
+        painter.success(action)
+    };
+    println!("Entry `{id}` in {backend} ({status})");
+    if let Some(entry) = entry {
+        println!();
+        println!("{}", painter.accent("Preview Output"));
+        println!("{}", render_entry(&entry));
     }
+    Ok(())
 }
 
-fn try_parse_every_minutes(input: &str) -> Option<CronSpec> {
-    static RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^every\s+(?:(?P<n>\d+)\s+)?min(?:ute)?s?$").unwrap());
-    RE.captures(input).map(|caps| {
-        let amount = caps
-            .name("n")
-            .map(|m| m.as_str().parse::<u32>().unwrap_or(1))
-            .unwrap_or(1)
-            .max(1);
-        let minute = if amount == 1 {
-            "*".to_string()
-        } else {
-            format!("*/{amount}")
+fn run_restore(painter: &Painter, backend: &Backend, json: bool, backups: &Backups) -> Result<()> {
+    let Some(backup) = restore_backup(backend, backups)? else {
+        bail!("No backups of {backend} in {}", backups.dir.display());
+    };
+    let remaining = list_backups(backend, backups)?.len();
+
+    if json {
+        let report = RestoreReport {
+            file: backend.to_string(),
+            backup,
+            remaining,
         };
-        CronSpec::new(
-            minute,
-            "*",
-            "*",
-            "*",
-            "*",
-            format!("Every {amount} minute(s)"),
-        )
-    })
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "Restored {backend} from {} ({})",
+        backup.display(),
+        painter.success(format!("{remaining} older backup(s) left"))
+    );
+    Ok(())
 }
 
-fn try_parse_hourly(input: &str) -> Option<CronSpec> {
-    static RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^(?:hourly|every\s+hour)(?:\s+at\s+:(?P<m>\d{1,2}))?$").unwrap());
-    RE.captures(input).map(|caps| {
-        let minute = caps
-            .name("m")
-            .map(|m| m.as_str().parse::<u32>().unwrap_or(0).min(59))
-            .unwrap_or(0);
-        CronSpec::new(
-            minute.to_string(),
-            "*",
-            "*",
-            "*",
-            "*",
-            if minute == 0 {
-                "Every hour on the hour".to_string()
-            } else {
-                format!("Every hour at :{:02}", minute)
-            },
-        )
+fn cronjob_options(cli: &Cli) -> Result<CronJobOptions> {
+    let Some(image) = cli.image.clone() else {
+        bail!("--target k8s needs --image <image>");
+    };
+    Ok(CronJobOptions {
+        image,
+        namespace: cli.namespace.clone(),
+        time_zone: cli.tz.map(|zone| zone.name().to_string()),
+        concurrency_policy: cli.concurrency_policy.map(ConcurrencyPolicy::from),
+        successful_jobs_history_limit: cli.successful_jobs_history_limit,
+        failed_jobs_history_limit: cli.failed_jobs_history_limit,
     })
 }
 
-fn try_parse_every_hours(input: &str) -> Option<CronSpec> {
-    static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"^every\s+(?P<n>\d+)\s+hours?(?:\s+at\s+:(?P<m>\d{1,2}))?$").unwrap()
-    });
-    RE.captures(input).map(|caps| {
-        let amount = caps
-            .name("n")
-            .and_then(|m| m.as_str().parse::<u32>().ok())
-            .filter(|&v| v > 0)
-            .unwrap_or(1);
-        let minute = caps
-            .name("m")
-            .map(|m| m.as_str().parse::<u32>().unwrap_or(0).min(59))
-            .unwrap_or(0);
-        CronSpec::new(
-            minute.to_string(),
-            if amount == 1 {
-                "*".to_string()
-            } else {
-                format!("*/{amount}")
-            },
-            "*",
-            "*",
-            "*",
-            if minute == 0 {
-                format!("Every {amount} hour(s)")
-            } else {
-                format!("Every {amount} hour(s) at :{:02}", minute)
-            },
-        )
+/// Pick the crontab to work on from `--backend`, `--user` and a `--file`.
+///
+/// Without `--backend`, a file means the file backend and a user means the
+/// crontab backend; otherwise `crontab` is used when it is on PATH and the
+/// detected cron file when it is not.
+fn select_backend(cli: &Cli, file: Option<&Path>) -> Result<Backend> {
+    let kind = match (cli.backend, file, &cli.user) {
+        (Some(BackendKind::Crontab), Some(_), _) => {
+            bail!("--file can't be used with --backend crontab")
+        }
+        (Some(BackendKind::File), _, Some(_)) => {
+            bail!("--user only applies to --backend crontab")
+        }
+        (Some(kind), _, _) => kind,
+        (None, Some(_), _) => BackendKind::File,
+        (None, None, Some(_)) => BackendKind::Crontab,
+        (None, None, None) if on_path("crontab") => BackendKind::Crontab,
+        (None, None, None) => BackendKind::File,
+    };
+    Ok(match kind {
+        BackendKind::Crontab => Backend::Crontab {
+            user: cli.user.clone(),
+        },
+        BackendKind::File => {
+            Backend::File(file.map(Path::to_path_buf).unwrap_or_else(detect_cron_file))
+        }
     })
 }
 
-fn try_parse_daily(input: &str) -> Option<CronSpec> {
-    static RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^(?:(?:every\s+)?day|daily)(?:\s+at\s+)?(?P<time>.+)$").unwrap());
-    RE.captures(input).and_then(|caps| {
-        let (hour, minute) = parse_time_fragment(caps.name("time")?.as_str())?;
-        Some(CronSpec::new(
-            minute.to_string(),
-            hour.to_string(),
-            "*",
-            "*",
-            "*",
-            format!("Daily at {}", format_clock(hour, minute)),
-        ))
-    })
+fn on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
 }
 
-fn try_parse_weekdayish(input: &str) -> Option<CronSpec> {
-    static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"^(?:(?:every\s+)?(?P<kind>weekdays?|weekends?))\s+(?:at\s+)?(?P<time>.+)$")
-            .unwrap()
-    });
-    RE.captures(input).and_then(|caps| {
-        let (hour, minute) = parse_time_fragment(caps.name("time")?.as_str())?;
-        let kind = caps.name("kind")?.as_str();
-        let (dow, label) = if kind.starts_with("weekend") {
-            ("6,0".to_string(), "weekends".to_string())
-        } else {
-            ("1-5".to_string(), "weekdays".to_string())
-        };
-        Some(CronSpec::new(
-            minute.to_string(),
-            hour.to_string(),
-            "*",
-            "*",
-            dow,
-            format!("{} at {}", capitalize(&label), format_clock(hour, minute)),
-        ))
-    })
+fn quote_command(parts: &[String]) -> Result<String> {
+    Ok(parts
+        .iter()
+        .map(|part| {
+            try_quote(part)
+                .map(|quoted| quoted.to_string())
+                .map_err(|err| anyhow!("Invalid command segment `{part}`: {err}"))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join(" "))
 }
 
-fn try_parse_specific_days(input: &str) -> Option<CronSpec> {
-    let (prefix, time_part) = input.split_once(" at ")?;
-    let dow_set = parse_day_list(prefix)?;
-    let (hour, minute) = parse_time_fragment(time_part)?;
-    let explanation = format!(
-        "{} at {}",
-        describe_days(&dow_set.days),
-        format_clock(hour, minute)
-    );
-    Some(CronSpec::new(
-        minute.to_string(),
-        hour.to_string(),
-        "*",
-        "*",
-        dow_set.cron_value,
-        explanation,
-    ))
-}
-
-fn try_parse_monthly(input: &str) -> Option<CronSpec> {
-    if !input.starts_with("monthly") {
-        return None;
-    }
-    let remainder = input.trim_start_matches("monthly").trim();
-    if remainder.is_empty() {
-        return None;
-    }
-
-    if let Some(rest) = remainder.strip_prefix("on ") {
-        let (dom_part, time_part) = rest.split_once(" at ")?;
-        let dom = parse_dom_list(dom_part)?;
-        let (hour, minute) = parse_time_fragment(time_part)?;
-        let explanation = format!(
-            "Monthly on {} at {}",
-            dom.human_value,
-            format_clock(hour, minute)
-        );
-        return Some(CronSpec::new(
-            minute.to_string(),
-            hour.to_string(),
-            dom.cron_value,
-            "*",
-            "*",
-            explanation,
-        ));
-    }
-
-    if let Some(time_part) = remainder.strip_prefix("at ") {
-        let (hour, minute) = parse_time_fragment(time_part)?;
-        return Some(CronSpec::new(
-            minute.to_string(),
-            hour.to_string(),
-            "1",
-            "*",
-            "*",
-            format!(
-                "Monthly on day 1 at {} (default day)",
-                format_clock(hour, minute)
+fn parse_schedule(expression: &str, dialect: Dialect) -> Result<CronSpec> {
+    parse_expression_in(expression, dialect).map_err(|err| {
+        let context = match err.span() {
+            Some(span) => format!(
+                "Could not parse expression\n{}",
+                underline(expression, span)
             ),
-        ));
-    }
-
-    None
-}
-
-fn try_parse_on_days(input: &str) -> Option<CronSpec> {
-    if !input.starts_with("on ") {
-        return None;
-    }
-    let remainder = input.trim_start_matches("on ").trim();
-    let (dom_part, time_part) = remainder.split_once(" at ")?;
-    let dom = parse_dom_list(dom_part)?;
-    let (hour, minute) = parse_time_fragment(time_part)?;
-    Some(CronSpec::new(
-        minute.to_string(),
-        hour.to_string(),
-        dom.cron_value,
-        "*",
-        "*",
-        format!("On {} at {}", dom.human_value, format_clock(hour, minute)),
-    ))
-}
-
-struct DayList {
-    cron_value: String,
-    days: Vec<u8>,
-}
-
-fn parse_day_list(prefix: &str) -> Option<DayList> {
-    let normalized = prefix
-        .replace(',', " ")
-        .replace('&', " ")
-        .replace(" and ", " ");
-    let stop_words = ["every", "each", "on", "week", "weeks", "weekly", "the"];
-    let mut days = Vec::new();
-    for token in normalized.split_whitespace() {
-        let lower = token.trim().to_lowercase();
-        if stop_words.contains(&lower.as_str()) {
-            continue;
-        }
-        let cleaned = if lower.ends_with('s') {
-            &lower[..lower.len() - 1]
-        } else {
-            lower.as_str()
+            None => format!("Could not parse expression `{expression}`"),
         };
-        if let Some(value) = day_number(cleaned) {
-            if !days.contains(&value) {
-                days.push(value);
-            }
-        } else {
-            return None;
-        }
-    }
-    if days.is_empty() {
-        return None;
-    }
-    days.sort();
-    let cron_value = days
-        .iter()
-        .map(|d| d.to_string())
-        .collect::<Vec<_>>()
-        .join(",");
-    Some(DayList { cron_value, days })
-}
-
-fn day_number(token: &str) -> Option<u8> {
-    match token {
-        "sun" | "sunday" => Some(0),
-        "mon" | "monday" => Some(1),
-        "tue" | "tues" | "tuesday" => Some(2),
-        "wed" | "weds" | "wednesday" => Some(3),
-        "thu" | "thur" | "thurs" | "thursday" => Some(4),
-        "fri" | "friday" => Some(5),
-        "sat" | "saturday" => Some(6),
-        _ => None,
-    }
-}
-
-struct DomList {
-    cron_value: String,
-    human_value: String,
-}
-
-fn parse_dom_list(raw: &str) -> Option<DomList> {
-    let normalized = raw
-        .replace(',', " ")
-        .replace(" and ", " ")
-        .replace("th", "")
-        .replace("rd", "")
-        .replace("nd", "")
-        .replace("st", "");
-    let mut values = Vec::new();
-    for token in normalized.split_whitespace() {
-        if token.chars().all(|c| !c.is_ascii_digit()) {
-            continue;
-        }
-        let digits = token
-            .chars()
-            .filter(|c| c.is_ascii_digit())
-            .collect::<String>();
-        if digits.is_empty() {
-            continue;
-        }
-        if let Ok(value) = digits.parse::<u32>() {
-            if (1..=31).contains(&value) && !values.contains(&value) {
-                values.push(value);
-            }
-        }
-    }
-    if values.is_empty() {
-        return None;
-    }
-    values.sort();
-    let cron_value = values
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>()
-        .join(",");
-    let human_value = values
-        .iter()
-        .map(|v| format!("{v}"))
-        .collect::<Vec<_>>()
-        .join(", ");
-    Some(DomList {
-        cron_value,
-        human_value,
+        anyhow::Error::new(err).context(context)
     })
 }
 
-fn parse_time_fragment(raw: &str) -> Option<(u32, u32)> {
-    let trimmed = raw.trim().to_lowercase();
-    if trimmed == "midnight" {
-        return Some((0, 0));
-    }
-    if trimmed == "noon" {
-        return Some((12, 0));
+fn print_pattern_guide(painter: &Painter) {
+    println!("{}", painter.accent("Supported phrasing samples:"));
+    let width = PATTERN_GUIDE
+        .iter()
+        .map(|(syntax, _)| syntax.len())
+        .max()
+        .unwrap_or(0);
+    for (syntax, example) in PATTERN_GUIDE {
+        println!(
+            "  - {:<width$} {}",
+            syntax,
+            painter.success(format!("e.g. {}", example))
+        );
     }
+}
 
-    let mut fragment = trimmed.replace(' ', "");
-    let mut meridian = None;
-    if let Some(rest) = fragment.strip_suffix("am") {
-        fragment = rest.to_string();
-        meridian = Some("am");
-    } else if let Some(rest) = fragment.strip_suffix("pm") {
-        fragment = rest.to_string();
-        meridian = Some("pm");
+#[allow(clippy::too_many_arguments)]
+fn print_summary(
+    painter: &Painter,
+    entry: &CronEntry,
+    cron_line: &str,
+    guard: Option<&str>,
+    preview: &str,
+    cli: &Cli,
+    written: Option<(&str, Option<WriteStatus>)>,
+    upcoming: &[DateTime<Local>],
+) {
+    println!("{}", painter.accent("Parsed Input"));
+    if cron_line.contains('\n') {
+        println!("Schedule: {}", entry.schedule.explanation);
+        for line in cron_line.lines() {
+            println!("  {}", painter.success(line));
+        }
+    } else {
+        println!(
+            "Schedule: {}  ({})",
+            painter.success(cron_line),
+            entry.schedule.explanation
+        );
     }
-
-    let mut parts = fragment.split(':');
-    let hour_part = parts.next()?;
-    let minute_part = parts.next();
-    if parts.next().is_some() {
-        return None;
+    if let Some(guard) = guard {
+        println!("Guard: {guard}");
+    }
+    println!("Command: {}", entry.command);
+    if let Some(comment) = &entry.comment {
+        println!("Comment: {}", comment);
     }
-    let hour = hour_part.parse::<u32>().ok()?;
-    if hour > 23 {
-        return None;
+    if !entry.env.is_empty() {
+        println!("  Env      : {}", format_env(&entry.env));
     }
-    let minute = match minute_part {
-        Some(value) => value.parse::<u32>().ok()?,
-        None => 0,
-    };
-    if minute > 59 {
-        return None;
+    if let Some((target, status)) = written {
+        let status = match status {
+            _ if cli.dry_run => painter.warn("dry run - not written"),
+            Some(WriteStatus::New) => painter.success("written"),
+            Some(WriteStatus::Replaced) => painter.success("replaced existing entry"),
+            Some(WriteStatus::Unchanged) => painter.warn("already present - unchanged"),
+            None => painter.warn("skipped"),
+        };
+        println!("  File     : {target} ({status})");
     }
-
-    let mut hour = hour;
-    if let Some(marker) = meridian {
-        if hour > 12 {
-            return None;
-        }
-        if marker == "am" {
-            if hour == 12 {
-                hour = 0;
+    if cli.next.is_some() {
+        if entry.schedule.is_reboot() {
+            println!("  Next runs: at system startup");
+        } else if upcoming.is_empty() {
+            println!("  Next runs: {}", painter.warn("none found"));
+        } else {
+            let format = if entry.schedule.second.is_some() {
+                "%Y-%m-%d %H:%M:%S %a"
+            } else {
+                "%Y-%m-%d %H:%M %a"
+            };
+            println!("  Next runs:");
+            for run in upcoming {
+                println!("    {}", run.format(format));
             }
-        } else if hour != 12 {
-            hour += 12;
         }
     }
-
-    Some((hour, minute))
+    println!();
+    println!("{}", painter.accent("Preview Output"));
+    println!("{preview}");
 }
 
-fn format_clock(hour: u32, minute: u32) -> String {
-    format!("{:02}:{:02}", hour, minute)
+/// The expression with a caret line under the bytes in `span`.
+fn underline(expression: &str, span: Range<usize>) -> String {
+    let lead = expression[..span.start].chars().count();
+    let width = expression[span].chars().count().max(1);
+    format!(
+        "    {expression}\n    {}{}",
+        " ".repeat(lead),
+        "^".repeat(width)
+    )
 }
 
-fn describe_days(days: &[u8]) -> String {
-    let labels = days
+fn parse_timestamp(raw: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(parsed.with_timezone(&Local));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
         .iter()
-        .map(|d| match d {
-            0 => "Sundays",
-            1 => "Mondays",
-            2 => "Tuesdays",
-            3 => "Wednesdays",
-            4 => "Thursdays",
-            5 => "Fridays",
-            _ => "Saturdays",
+        .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
         })
-        .collect::<Vec<_>>();
-    if labels.len() == 1 {
-        labels[0].to_string()
-    } else {
-        labels.join(", ")
+        .ok_or_else(|| "Expected RFC 3339 or YYYY-MM-DD[ HH:MM[:SS]]".to_string())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("{naive} does not exist in the local time zone"))
+}
+
+fn parse_id(raw: &str) -> Result<String, String> {
+    if raw.is_empty()
+        || !raw
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        return Err("Use letters, digits, `-`, `_` or `.`".to_string());
     }
+    Ok(raw.to_string())
 }
 
-fn capitalize(text: &str) -> String {
-    let mut chars = text.chars();
-    match chars.next() {
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-        None => String::new(),
+/// A systemd time span such as `30s`, `5m` or `1h 30min`.
+fn parse_time_span(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    let mut rest = raw;
+    while !rest.is_empty() {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            return Err("Expected a time span such as `30s`, `5m` or `1h 30min`".to_string());
+        }
+        rest = rest[digits..]
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .trim_start();
+    }
+    if raw.is_empty() {
+        return Err("Time span cannot be empty".to_string());
     }
+    Ok(raw.to_string())
+}
+
+/// An IANA zone name such as `UTC` or `America/New_York`.
+fn parse_time_zone(raw: &str) -> Result<Tz, String> {
+    raw.parse()
+        .map_err(|_| "Expected an IANA time zone such as `Europe/Berlin`".to_string())
 }
 
 fn parse_env_var(raw: &str) -> Result<EnvVar, String> {
@@ -0,0 +1,275 @@
+use crate::error::ParseError;
+use crate::field::{Base, Field, FieldSet, spec_extras, spec_fields};
+use crate::spec::CronSpec;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Weekday};
+
+/// How far ahead to look before concluding a schedule never fires. Weekday and
+/// leap-day combinations repeat on a 28-year cycle.
+const SEARCH_DAYS: usize = 366 * 28;
+
+/// The next `count` times `spec` fires strictly after `after`, in `after`'s zone.
+///
+/// All five fields are evaluated, plus the seconds and year of a Quartz
+/// schedule and its `L`, `W` and `#` days. When both day-of-month and
+/// day-of-week are restricted a day matches if either does, as in Vixie cron.
+/// Wall-clock times that do not exist in the zone (a DST gap) are skipped;
+/// repeated ones fire once, at the earlier instant. `@reboot` has no calendar
+/// runs, so it yields none. A schedule with a [`CronSpec::time_zone`] is
+/// evaluated in that zone and its runs converted back to `after`'s, and the
+/// runs of a schedule with several lines are merged.
+pub fn next_runs<Tz: TimeZone>(
+    spec: &CronSpec,
+    after: &DateTime<Tz>,
+    count: usize,
+) -> Result<Vec<DateTime<Tz>>, ParseError> {
+    if spec.is_reboot() {
+        return Ok(Vec::new());
+    }
+    if let Some(zone) = spec.zone() {
+        let local = CronSpec {
+            time_zone: None,
+            ..spec.clone()
+        };
+        let runs = next_runs(&local, &after.with_timezone(&zone), count)?;
+        return Ok(runs
+            .into_iter()
+            .map(|run| run.with_timezone(&after.timezone()))
+            .collect());
+    }
+    if !spec.extra_lines.is_empty() {
+        let mut runs = Vec::new();
+        for line in spec.lines() {
+            runs.extend(next_runs(&line, after, count)?);
+        }
+        runs.sort();
+        runs.dedup();
+        runs.truncate(count);
+        return Ok(runs);
+    }
+    let fields = Fields::from_spec(spec)?;
+    let zone = after.timezone();
+    let local = after.naive_local();
+    let start = local.with_nanosecond(0).unwrap_or(local) + Duration::seconds(1);
+
+    let mut runs = Vec::new();
+    if count == 0 {
+        return Ok(runs);
+    }
+    for date in start.date().iter_days().take(SEARCH_DAYS) {
+        if !fields.day_matches(date) {
+            continue;
+        }
+        for hour in fields.hour.values() {
+            for minute in fields.minute.values() {
+                for second in fields.second.values() {
+                    let Some(naive) = date.and_hms_opt(hour, minute, second) else {
+                        continue;
+                    };
+                    if naive < start {
+                        continue;
+                    }
+                    let Some(instant) = zone.from_local_datetime(&naive).earliest() else {
+                        continue;
+                    };
+                    runs.push(instant);
+                    if runs.len() == count {
+                        return Ok(runs);
+                    }
+                }
+            }
+        }
+    }
+    Ok(runs)
+}
+
+/// A schedule's fields, ready to be matched against dates and times.
+pub(crate) struct Fields {
+    pub(crate) second: FieldSet,
+    pub(crate) minute: FieldSet,
+    pub(crate) hour: FieldSet,
+    day_of_month: FieldSet,
+    month: FieldSet,
+    day_of_week: FieldSet,
+    year: Option<Field>,
+    /// `L`, `W` and `#` items from either day field.
+    special_days: Vec<Base>,
+}
+
+impl Fields {
+    pub(crate) fn from_spec(spec: &CronSpec) -> Result<Self, ParseError> {
+        let [minute, hour, day_of_month, month, day_of_week] = spec_fields(spec)?;
+        let (second, year) = spec_extras(spec)?;
+        let special_days = [&day_of_month, &day_of_week]
+            .into_iter()
+            .flat_map(|field| &field.items)
+            .map(|item| item.base)
+            .filter(|base| base.is_special())
+            .collect();
+        Ok(Self {
+            second: second.map_or_else(|| FieldSet::single(0), |second| second.set()),
+            minute: minute.set(),
+            hour: hour.set(),
+            day_of_month: day_of_month.set(),
+            month: month.set(),
+            day_of_week: day_of_week.set(),
+            year,
+            special_days,
+        })
+    }
+
+    pub(crate) fn day_matches(&self, date: NaiveDate) -> bool {
+        if !self.month.contains(date.month()) {
+            return false;
+        }
+        if let Some(year) = &self.year
+            && !u32::try_from(date.year()).is_ok_and(|value| year.allows(value))
+        {
+            return false;
+        }
+        let special = |dom: bool| {
+            self.special_days
+                .iter()
+                .filter(|base| matches!(base, Base::Nth(..) | Base::LastOf(_)) != dom)
+                .any(|base| special_day_matches(*base, date))
+        };
+        let dom = self.day_of_month.contains(date.day()) || special(true);
+        let dow = self
+            .day_of_week
+            .contains(date.weekday().num_days_from_sunday())
+            || special(false);
+        if self.day_of_month.star || self.day_of_week.star {
+            dom && dow
+        } else {
+            dom || dow
+        }
+    }
+}
+
+/// Whether `date` is the day a date-dependent item picks in its month.
+fn special_day_matches(base: Base, date: NaiveDate) -> bool {
+    let last = last_day_of_month(date);
+    let weekday_of = |day: u32| {
+        date.with_day(day)
+            .expect("day is within the month")
+            .weekday()
+    };
+    match base {
+        Base::Last(offset) => date.day() + offset == last,
+        Base::LastWeekday => {
+            let day = match weekday_of(last) {
+                Weekday::Sat => last - 1,
+                Weekday::Sun => last - 2,
+                _ => last,
+            };
+            date.day() == day
+        }
+        Base::NearestWeekday(day) if day <= last => {
+            // Quartz never moves to a neighbouring month: `1W` on a Saturday
+            // is Monday the 3rd.
+            let day = match weekday_of(day) {
+                Weekday::Sat if day == 1 => 3,
+                Weekday::Sat => day - 1,
+                Weekday::Sun if day == last => day - 2,
+                Weekday::Sun => day + 1,
+                _ => day,
+            };
+            date.day() == day
+        }
+        Base::Nth(weekday, nth) => {
+            date.weekday().num_days_from_sunday() == weekday % 7 && (date.day() - 1) / 7 + 1 == nth
+        }
+        Base::LastOf(weekday) => {
+            date.weekday().num_days_from_sunday() == weekday % 7 && date.day() + 7 > last
+        }
+        _ => false,
+    }
+}
+
+/// The number of days in `date`'s month.
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::parse::{parse_expression, parse_expression_in};
+    use chrono::Utc;
+    use chrono_tz::Europe::Berlin;
+
+    fn runs<Tz: TimeZone>(spec: &CronSpec, after: DateTime<Tz>, count: usize) -> Vec<String>
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        next_runs(spec, &after, count)
+            .unwrap()
+            .iter()
+            .map(|run| run.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn runs_are_strictly_after_the_start() {
+        let spec = parse_expression("30 9 * * 1-5").unwrap();
+        let friday = Utc.with_ymd_and_hms(2026, 10, 16, 9, 30, 0).unwrap();
+        assert_eq!(
+            runs(&spec, friday, 2),
+            ["2026-10-19 09:30", "2026-10-20 09:30"]
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        let spec = parse_expression("0 0 13 * 5").unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap();
+        assert_eq!(
+            runs(&spec, after, 4),
+            [
+                "2026-10-23 00:00",
+                "2026-10-30 00:00",
+                "2026-11-06 00:00",
+                "2026-11-13 00:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn quartz_month_end_days_are_evaluated() {
+        let spec = parse_expression_in("0 0 12 L * ?", Dialect::Quartz).unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            runs(&spec, after, 2),
+            ["2026-02-28 12:00", "2026-03-31 12:00"]
+        );
+    }
+
+    #[test]
+    fn times_lost_to_a_dst_gap_are_skipped() {
+        let spec = parse_expression("30 2 * * *").unwrap();
+        let after = Berlin.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap();
+        assert_eq!(
+            runs(&spec, after, 2),
+            ["2026-03-30 02:30", "2026-03-31 02:30"]
+        );
+    }
+
+    #[test]
+    fn zoned_and_multi_line_schedules_come_back_in_the_callers_zone() {
+        let spec = parse_expression("daily at 09:00 and 17:30 Europe/Berlin").unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 7, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            runs(&spec, after, 3),
+            ["2026-07-01 07:00", "2026-07-01 15:30", "2026-07-02 07:00"]
+        );
+        let reboot = parse_expression("@reboot").unwrap();
+        assert!(runs(&reboot, after, 3).is_empty());
+    }
+}
@@ -0,0 +1,1962 @@
+use crate::calendar::month_length;
+use crate::describe::{describe_fields, describe_months, explain, join_and};
+use crate::dialect::{Dialect, try_parse_dialect};
+use crate::error::ParseError;
+use crate::field::{FieldKind, compress, parse_fields};
+use crate::spec::CronSpec;
+use chrono_tz::{TZ_VARIANTS, Tz};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Accepted phrasing shapes, each paired with an example.
+pub const PATTERN_GUIDE: &[(&str, &str)] = &[
+    ("daily at HH:MM", "daily at 05:30"),
+    ("weekdays at HH:MM", "weekdays at 07:15"),
+    ("weekends at HH:MM", "weekends at 19:05"),
+    ("<days> at HH:MM", "monday wednesday at 03:00"),
+    ("weekly on <days> at HH:MM", "weekly on fri at 02:45"),
+    (
+        "monthly on <dates> at HH:MM",
+        "monthly on 1st and 15th at 04:00",
+    ),
+    ("on <dates> at HH:MM", "on 10,20 at 22:30"),
+    ("every N minutes", "every 15 minutes"),
+    ("every N hours", "every 2 hours"),
+    (
+        "every N minutes starting at :MM",
+        "every 15 minutes starting at :05",
+    ),
+    ("every N hours from HH:MM", "every 2 hours from 01:00"),
+    (
+        "every N minutes between X and Y",
+        "every 15 minutes between 9am and 5pm on weekdays",
+    ),
+    (
+        "every N hours from X to Y",
+        "every 2 hours from 08:00 to 18:00",
+    ),
+    (
+        "every N minutes during business hours",
+        "every 30 minutes during business hours",
+    ),
+    ("hourly at :MM", "hourly at :10"),
+    ("<schedule> at <times>", "weekdays at 09:00 and 17:30"),
+    (
+        "yearly on <month> <date> [at HH:MM]",
+        "yearly on dec 25 at 08:00",
+    ),
+    (
+        "on the <dates> of <months> [at HH:MM]",
+        "on the 1st of january and july",
+    ),
+    ("every <months> [on the <date>]", "every march on the 15th"),
+    ("quarterly [on the <date>] [at HH:MM]", "quarterly at 06:00"),
+    ("semiannually [on the <date>]", "semiannually on the 15th"),
+    (
+        "<schedule> in <months>",
+        "daily at 9am in june through august",
+    ),
+    (
+        "last day of the month [at HH:MM]",
+        "last day of the month at 23:00",
+    ),
+    ("N days before month end", "2 days before month end"),
+    (
+        "<nth> <day> of the month [at HH:MM]",
+        "first sunday of the month at 03:00",
+    ),
+    ("daily/weekly/monthly/yearly", "yearly"),
+    ("at boot / on startup", "on startup"),
+    ("@macro", "@daily"),
+    ("<schedule> <zone>", "weekdays at 09:00 Europe/Berlin"),
+    ("raw cron", "30 3 * * 1"),
+    ("raw Quartz (--dialect)", "0 15 7 ? * MON-FRI"),
+];
+
+/// Parse a natural-language schedule or a raw five-field cron expression.
+///
+/// When a phrase is recognised but one of its parts is invalid, the error
+/// points at that part; spans are byte offsets into `expression`. A trailing
+/// IANA zone, as in `weekdays at 09:00 Europe/Berlin`, ends up in
+/// [`CronSpec::time_zone`].
+///
+/// ```
+/// let spec = cronoisseur::parse_expression("weekdays at 07:15").unwrap();
+/// assert_eq!(spec.as_string(), "15 7 * * 1-5");
+/// let berlin = cronoisseur::parse_expression("weekdays at 09:00 Europe/Berlin").unwrap();
+/// assert_eq!(berlin.time_zone.as_deref(), Some("Europe/Berlin"));
+/// ```
+pub fn parse_expression(expression: &str) -> Result<CronSpec, ParseError> {
+    parse_expression_in(expression, Dialect::Vixie)
+}
+
+/// Like [`parse_expression`], but raw expressions are read as `dialect`.
+///
+/// ```
+/// use cronoisseur::{Dialect, parse_expression_in};
+///
+/// let spec = parse_expression_in("0 15 7 ? * MON-FRI", Dialect::Quartz).unwrap();
+/// assert_eq!(spec.as_string(), "15 7 * * MON-FRI");
+/// ```
+pub fn parse_expression_in(expression: &str, dialect: Dialect) -> Result<CronSpec, ParseError> {
+    let (expression, zone) = split_zone(expression)?;
+    let spec = parse_schedule(expression, dialect)?;
+    Ok(match zone {
+        Some(zone) => spec.with_time_zone(zone),
+        None => spec,
+    })
+}
+
+/// Parse everything but a trailing time zone.
+fn parse_schedule(expression: &str, dialect: Dialect) -> Result<CronSpec, ParseError> {
+    let trimmed = expression.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    if let Some(spec) = try_parse_macro(expression)? {
+        return Ok(spec);
+    }
+    let raw = match dialect {
+        Dialect::Vixie => try_parse_raw(expression)?,
+        _ => try_parse_dialect(expression, dialect)?,
+    };
+    if let Some(spec) = raw {
+        return Ok(spec);
+    }
+
+    let normalized = Normalized::new(expression);
+    let result = split_months(&normalized.text).and_then(|(text, months)| {
+        let mut first_error = None;
+        for parser in PARSERS {
+            match parser(text) {
+                Ok(Some(spec)) => {
+                    return match &months {
+                        Some(months) => in_months(spec, months).ok_or(ParseError::Unsupported {
+                            span: 0..normalized.text.len(),
+                        }),
+                        None => Ok(spec),
+                    };
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_error.unwrap_or(ParseError::Unsupported {
+            span: 0..normalized.text.len(),
+        }))
+    });
+    result.map_err(|err| err.map_span(|span| normalized.original_span(span)))
+}
+
+/// Split a trailing `in <months>` or `during <months>` off a phrase, as in
+/// `daily at 9am in june through august`.
+fn split_months(text: &str) -> Result<(&str, Option<MonthList>), ParseError> {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?P<rest>.+)\s+(?:in|during)\s+(?:the\s+)?(?P<months>[a-z][a-z ,&-]*)$")
+            .unwrap()
+    });
+    let Some(caps) = RE.captures(text) else {
+        return Ok((text, None));
+    };
+    let months = caps.name("months").map_or("", |m| m.as_str());
+    Ok(match parse_month_list(text, months)? {
+        Some(list) => (caps.name("rest").map_or(text, |m| m.as_str()), Some(list)),
+        None => (text, None),
+    })
+}
+
+/// Limit every line of `spec` to `months`; `None` when it already names a
+/// month or runs at startup.
+fn in_months(spec: CronSpec, months: &MonthList) -> Option<CronSpec> {
+    if spec.is_reboot() || spec.lines().iter().any(|line| line.month != "*") {
+        return None;
+    }
+    let label = describe_months(&months.values);
+    let explanation = match spec.macro_name {
+        Some(_) => {
+            let fields = [
+                spec.minute.as_str(),
+                spec.hour.as_str(),
+                spec.day_of_month.as_str(),
+                months.cron_value.as_str(),
+                spec.day_of_week.as_str(),
+            ];
+            explain(&fields.join(" ")).ok()?
+        }
+        None => format!("{} in {label}", spec.explanation),
+    };
+    let lines = spec
+        .lines()
+        .into_iter()
+        .map(|line| CronSpec {
+            macro_name: None,
+            month: months.cron_value.clone(),
+            explanation: format!("{} in {label}", line.explanation),
+            ..line
+        })
+        .collect();
+    Some(CronSpec::from_lines(lines, explanation))
+}
+
+/// Split a trailing IANA zone such as `Europe/Berlin` or `in UTC` off an
+/// expression. Names are matched case-insensitively; one that looks like a
+/// zone in a known region but isn't is an error.
+fn split_zone(expression: &str) -> Result<(&str, Option<Tz>), ParseError> {
+    static ZONE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)\s+(?:in\s+)?(?P<zone>utc|gmt|[a-z][a-z0-9_+-]*(?:/[a-z0-9_+-]+)+)\s*$")
+            .unwrap()
+    });
+    let Some(caps) = ZONE.captures(expression) else {
+        return Ok((expression, None));
+    };
+    let name = &caps["zone"];
+    let rest = &expression[..caps.get(0).unwrap().start()];
+    if let Some(zone) = TZ_VARIANTS
+        .iter()
+        .find(|zone| zone.name().eq_ignore_ascii_case(name))
+    {
+        return Ok((rest, Some(*zone)));
+    }
+    let region = name.split('/').next().unwrap_or_default();
+    let known_region = TZ_VARIANTS.iter().any(|zone| {
+        zone.name()
+            .split_once('/')
+            .is_some_and(|(candidate, _)| candidate.eq_ignore_ascii_case(region))
+    });
+    if known_region {
+        return Err(ParseError::UnknownTimeZone {
+            span: caps.name("zone").unwrap().range(),
+            name: name.to_string(),
+        });
+    }
+    Ok((expression, None))
+}
+
+/// Outcome of a single phrase parser: `Ok(None)` when the input does not
+/// have its shape, an error when it does but a part of it is invalid.
+type Attempt = Result<Option<CronSpec>, ParseError>;
+
+const PARSERS: &[fn(&str) -> Attempt] = &[
+    try_parse_macro_phrase,
+    try_parse_interval_window,
+    try_parse_every_minutes,
+    try_parse_hourly,
+    try_parse_every_hours,
+    try_parse_daily,
+    try_parse_weekdayish,
+    try_parse_specific_days,
+    try_parse_monthly,
+    try_parse_month_end,
+    try_parse_ordinal_weekday,
+    try_parse_yearly_on,
+    try_parse_period_of_year,
+    try_parse_of_months,
+    try_parse_on_days,
+    try_parse_every_month_name,
+];
+
+/// Lowercased, whitespace-collapsed copy of an expression that remembers
+/// where each of its bytes came from.
+struct Normalized {
+    text: String,
+    origins: Vec<Range<usize>>,
+}
+
+impl Normalized {
+    fn new(raw: &str) -> Self {
+        let mut text = String::new();
+        let mut origins = Vec::new();
+        let mut pending_space = false;
+        for (offset, ch) in raw.char_indices() {
+            let origin = offset..offset + ch.len_utf8();
+            if ch.is_whitespace() {
+                pending_space = !text.is_empty();
+                continue;
+            }
+            if pending_space {
+                text.push(' ');
+                origins.push(origin.start..origin.start);
+                pending_space = false;
+            }
+            let mapped = if matches!(ch, '–' | '—') { '-' } else { ch };
+            for lower in mapped.to_lowercase() {
+                let before = text.len();
+                text.push(lower);
+                origins.extend((before..text.len()).map(|_| origin.clone()));
+            }
+        }
+        Self { text, origins }
+    }
+
+    fn original_span(&self, span: Range<usize>) -> Range<usize> {
+        let Some(last) = self.origins.last() else {
+            return 0..0;
+        };
+        let start = self
+            .origins
+            .get(span.start)
+            .map_or(last.end, |origin| origin.start);
+        let end = match span.end.checked_sub(1).and_then(|i| self.origins.get(i)) {
+            Some(origin) if span.end > span.start => origin.end,
+            _ => start,
+        };
+        start..end.max(start)
+    }
+}
+
+/// Byte range of `part` within `source`; `part` must be a subslice of it.
+pub(crate) fn span_in(source: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - source.as_ptr() as usize;
+    debug_assert!(start + part.len() <= source.len());
+    start..start + part.len()
+}
+
+/// Recognise a five-field cron expression and validate every field.
+///
+/// Input counts as raw cron when it has five cron-alphabet words, its
+/// minute starts with a digit or `*` and not all the others are plain words; from then on problems are errors rather
+/// than a fall-through to the phrase parsers.
+fn try_parse_raw(input: &str) -> Attempt {
+    let parts: Vec<_> = input.split_whitespace().collect();
+    let Ok(parts) = <[&str; 5]>::try_from(parts) else {
+        return Ok(None);
+    };
+    if !looks_raw(&parts) {
+        return Ok(None);
+    }
+
+    let fields = parse_fields(input, parts, false)?;
+    Ok(Some(CronSpec::new(
+        parts[0],
+        parts[1],
+        parts[2],
+        parts[3],
+        parts[4],
+        describe_fields(&fields),
+    )))
+}
+
+/// The five-field equivalent of every schedule macro cron accepts besides
+/// `@reboot`.
+const MACROS: &[(&str, &str)] = &[
+    ("@yearly", "0 0 1 1 *"),
+    ("@annually", "0 0 1 1 *"),
+    ("@monthly", "0 0 1 * *"),
+    ("@weekly", "0 0 * * 0"),
+    ("@daily", "0 0 * * *"),
+    ("@midnight", "0 0 * * *"),
+    ("@hourly", "0 * * * *"),
+];
+
+/// Recognise a schedule macro such as `@daily` or `@reboot`.
+fn try_parse_macro(input: &str) -> Attempt {
+    let trimmed = input.trim();
+    if !trimmed.starts_with('@') {
+        return Ok(None);
+    }
+    macro_spec(trimmed)
+        .map(Some)
+        .ok_or_else(|| ParseError::UnknownMacro {
+            span: span_in(input, trimmed),
+            name: trimmed.to_string(),
+        })
+}
+
+/// The schedule a macro stands for, keeping the macro itself.
+fn macro_spec(name: &str) -> Option<CronSpec> {
+    let name = name.to_ascii_lowercase();
+    if name == "@reboot" {
+        return Some(CronSpec::reboot());
+    }
+    let (_, expression) = MACROS.iter().find(|(candidate, _)| *candidate == name)?;
+    let parts: [&str; 5] = expression
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .try_into()
+        .ok()?;
+    let fields = parse_fields(expression, parts, false).ok()?;
+    let explanation = format!("{}, as {name}", describe_fields(&fields));
+    Some(CronSpec {
+        macro_name: Some(name),
+        ..CronSpec::new(
+            parts[0],
+            parts[1],
+            parts[2],
+            parts[3],
+            parts[4],
+            explanation,
+        )
+    })
+}
+
+/// `at boot`, `on startup`, `yearly` and friends, which read as macros.
+fn try_parse_macro_phrase(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^(?:(?P<boot>(?:at|on|after)\s+(?:system\s+)?(?:boot|startup|start-up|reboot))|(?P<period>yearly|annually|every\s+year|monthly|every\s+month|weekly|every\s+week|daily))$",
+        )
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    if caps.name("boot").is_some() {
+        return Ok(Some(CronSpec::reboot()));
+    }
+    let name = match &caps["period"] {
+        "yearly" | "annually" | "every year" => "@yearly",
+        "monthly" | "every month" => "@monthly",
+        "weekly" | "every week" => "@weekly",
+        _ => "@daily",
+    };
+    Ok(macro_spec(name))
+}
+
+/// Whether every word is in the cron alphabet, the first starts with a
+/// digit or `*` and the rest aren't all plain words, as in `2 days before
+/// month end`.
+pub(crate) fn looks_raw(parts: &[&str]) -> bool {
+    parts
+        .first()
+        .is_some_and(|first| first.starts_with(|c: char| c.is_ascii_digit() || c == '*'))
+        && !parts[1..]
+            .iter()
+            .all(|part| part.chars().all(|c| c.is_ascii_alphabetic()))
+        && parts.iter().all(|segment| {
+            segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "*?/,-#".contains(c))
+        })
+}
+
+/// `every N minutes`, optionally `starting at :05`, `from 09:10` or `offset
+/// by 5 minutes`. Intervals that divide the hour are `*/N`, or `5-59/N` with
+/// an offset; ones that divide the day, like 45 or 90 minutes, are spelled
+/// out over several lines so the runs stay evenly spaced. Anything else
+/// starts over every hour, or at midnight for intervals over an hour, which
+/// the explanation says. Intervals longer than a day run once a day, with a
+/// warning.
+fn try_parse_every_minutes(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(
+            r"^every\s+(?:(?P<n>\d+)\s+)?min(?:ute)?s?{OFFSET}$"
+        ))
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let amount = caps
+        .name("n")
+        .map(|m| m.as_str().parse::<u32>().unwrap_or(u32::MAX))
+        .unwrap_or(1)
+        .max(1);
+    let offset = match (caps.name("how"), caps.name("offset")) {
+        (Some(how), Some(offset)) => match interval_offset(input, how.as_str(), offset.as_str())? {
+            Some(offset) => Some(offset),
+            None => return Ok(None),
+        },
+        _ => None,
+    };
+    if let Some(offset) = offset {
+        return offset_interval(amount, "minute", offset).map(Some);
+    }
+    if amount > MINUTES_PER_DAY {
+        return Ok(Some(daily_restart(amount, "minute", 0)));
+    }
+    if amount < 60 && !60u32.is_multiple_of(amount) && !MINUTES_PER_DAY.is_multiple_of(amount) {
+        let last = 59 / amount * amount;
+        return Ok(Some(CronSpec::new(
+            format!("*/{amount}"),
+            "*",
+            "*",
+            "*",
+            "*",
+            format!(
+                "Every {amount} minute(s) from :00 to :{last:02}, starting over every hour ({} minutes after the last run)",
+                60 - last
+            ),
+        )));
+    }
+    if amount > 1 && !60u32.is_multiple_of(amount) {
+        let runs: Vec<u32> = (0..MINUTES_PER_DAY).step_by(amount as usize).collect();
+        let lines = run_lines(&runs, None)?;
+        let explanation = if MINUTES_PER_DAY.is_multiple_of(amount) {
+            format!("Every {amount} minute(s)")
+        } else {
+            let last = runs[runs.len() - 1];
+            format!(
+                "Every {amount} minute(s) from 00:00 to {}, starting over at midnight ({} minutes after the last run)",
+                format_clock(last / 60, last % 60),
+                MINUTES_PER_DAY - last
+            )
+        };
+        return Ok(Some(CronSpec::from_lines(lines, explanation)));
+    }
+    let minute = if amount == 1 {
+        "*".to_string()
+    } else {
+        format!("*/{amount}")
+    };
+    Ok(Some(CronSpec::new(
+        minute,
+        "*",
+        "*",
+        "*",
+        "*",
+        format!("Every {amount} minute(s)"),
+    )))
+}
+
+/// An interval limited to a window of the day, as in `every 15 minutes
+/// between 9am and 5pm on weekdays`, `every 2 hours from 08:00 to 18:00` or
+/// `every 30 minutes during business hours` (09:00 to 17:00, on weekdays
+/// unless other days are named).
+///
+/// Runs start at the window's opening and stop before its end. Hours whose
+/// runs fall on the same minutes share a cron line, so a window opening or
+/// closing off the hour takes several lines, and one that wraps past
+/// midnight carries its early hours over to the following days.
+fn try_parse_interval_window(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"^every\s+(?:(?P<n>\d+)\s+)?(?P<unit>min(?:ute)?s?|hours?)\s+",
+            r"(?:(?:between|from)\s+(?P<start>\S+(?:\s*[ap]m)?)\s+(?:and|to|until|till|-)\s+(?P<end>\S+(?:\s*[ap]m)?)",
+            r"|during\s+(?:business|office|working)\s+hours)",
+            r"(?:\s+(?P<days>.+))?$",
+        ))
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let amount = caps
+        .name("n")
+        .map_or(1, |m| m.as_str().parse::<u32>().unwrap_or(u32::MAX))
+        .max(1);
+    let (step, unit) = if caps["unit"].starts_with('h') {
+        (amount.saturating_mul(60), "hour")
+    } else {
+        (amount, "minute")
+    };
+    let (start, end) = match (caps.name("start"), caps.name("end")) {
+        (Some(start), Some(end)) => (
+            parse_time_fragment(input, start.as_str())?,
+            parse_time_fragment(input, end.as_str())?,
+        ),
+        _ => ((9, 0), (17, 0)),
+    };
+    let business = caps.name("start").is_none();
+    let (days, label) = match caps.name("days") {
+        Some(days) => match window_days(input, days.as_str())? {
+            Some(found) => found,
+            None => return Ok(None),
+        },
+        None if business => (Some(vec![1, 2, 3, 4, 5]), " on weekdays".to_string()),
+        None => (None, String::new()),
+    };
+
+    let start = start.0 * 60 + start.1;
+    let mut end = end.0 * 60 + end.1;
+    if end <= start {
+        end += MINUTES_PER_DAY;
+    }
+    let runs: Vec<u32> = (start..end).step_by(step as usize).collect();
+    let lines = run_lines(&runs, days.as_deref())?;
+    let clock = |run: u32| format_clock(run % MINUTES_PER_DAY / 60, run % 60);
+    let (first, last) = (runs[0], runs[runs.len() - 1]);
+    let explanation = if first == last {
+        format!("Once at {}{label}", clock(first))
+    } else {
+        format!(
+            "Every {amount} {unit}(s) from {} to {}{label}",
+            clock(first),
+            clock(last)
+        )
+    };
+    Ok(Some(CronSpec::from_lines(lines, explanation)))
+}
+
+/// Cron lines firing at each of `runs`, given in minutes after midnight, on
+/// `days` (cron day numbers, `None` for every day). Runs past midnight fall
+/// on the following days; hours whose runs share their minutes share a line.
+fn run_lines(runs: &[u32], days: Option<&[u32]>) -> Result<Vec<CronSpec>, ParseError> {
+    // Run times by the day they fall on (0, or 1 past midnight), then hour.
+    let mut by_day: BTreeMap<u32, BTreeMap<u32, Vec<u32>>> = BTreeMap::new();
+    for &run in runs {
+        by_day
+            .entry(run / MINUTES_PER_DAY)
+            .or_default()
+            .entry(run % MINUTES_PER_DAY / 60)
+            .or_default()
+            .push(run % 60);
+    }
+    let mut lines = Vec::new();
+    for (offset, hours) in by_day {
+        let day_of_week = match days {
+            Some(days) => {
+                let shifted: Vec<u32> = days.iter().map(|day| (day + offset) % 7).collect();
+                compress(FieldKind::DayOfWeek, &shifted)
+            }
+            None => "*".to_string(),
+        };
+        let mut groups: Vec<(Vec<u32>, Vec<u32>)> = Vec::new();
+        for (hour, minutes) in hours {
+            match groups.iter_mut().find(|(shared, _)| *shared == minutes) {
+                Some((_, hours)) => hours.push(hour),
+                None => groups.push((minutes, vec![hour])),
+            }
+        }
+        for (minutes, hours) in groups {
+            let fields = [
+                compress(FieldKind::Minute, &minutes),
+                compress(FieldKind::Hour, &hours),
+                "*".to_string(),
+                "*".to_string(),
+                day_of_week.clone(),
+            ];
+            let explanation = explain(&fields.join(" "))?;
+            let [minute, hour, day_of_month, month, day_of_week] = fields;
+            lines.push(CronSpec::new(
+                minute,
+                hour,
+                day_of_month,
+                month,
+                day_of_week,
+                explanation,
+            ));
+        }
+    }
+    Ok(lines)
+}
+
+/// The weekdays after an interval window (`on weekdays`, `on mon and wed`,
+/// `daily`), as cron day numbers (`None` for every day) and a label for the
+/// explanation. `Ok(None)` when the text names no days.
+#[allow(clippy::type_complexity)]
+fn window_days(source: &str, raw: &str) -> Result<Option<(Option<Vec<u32>>, String)>, ParseError> {
+    let raw = raw.strip_prefix("on ").unwrap_or(raw);
+    Ok(Some(match raw.strip_prefix("every ").unwrap_or(raw) {
+        "day" | "days" | "daily" => (None, String::new()),
+        "weekday" | "weekdays" => (Some(vec![1, 2, 3, 4, 5]), " on weekdays".to_string()),
+        "weekend" | "weekends" => (Some(vec![6, 0]), " on weekends".to_string()),
+        _ => match parse_day_list(source, raw)? {
+            Some(list) => (
+                Some(list.days.iter().map(|day| u32::from(*day)).collect()),
+                format!(" on {}", describe_days(&list.days)),
+            ),
+            None => return Ok(None),
+        },
+    }))
+}
+
+fn try_parse_hourly(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^(?:hourly|every\s+hour)(?:\s+at\s+(?P<m>:\d{1,2}(?:\s*(?:,|&|and)\s*:\d{1,2})*))?$",
+        )
+        .unwrap()
+    });
+    static MINUTE: Lazy<Regex> = Lazy::new(|| Regex::new(r":(\d{1,2})").unwrap());
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let mut minutes = match caps.name("m") {
+        Some(list) => MINUTE
+            .captures_iter(list.as_str())
+            .map(|minute| parse_minute(input, minute.get(1).map_or("", |m| m.as_str())))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![0],
+    };
+    minutes.sort_unstable();
+    minutes.dedup();
+    let explanation = if minutes == [0] {
+        "Every hour on the hour".to_string()
+    } else {
+        let labels: Vec<String> = minutes.iter().map(|m| format!(":{m:02}")).collect();
+        format!("Every hour at {}", join_and(&labels))
+    };
+    let minute = minutes
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Some(CronSpec::new(minute, "*", "*", "*", "*", explanation)))
+}
+
+/// `every N hours [at :MM]`, optionally `starting at 01:00`, `from 01:30`
+/// or `offset by 1 hour`. Intervals that don't divide the day start over at
+/// midnight, which the explanation says, so ones longer than a day run once
+/// a day, with a warning.
+fn try_parse_every_hours(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(
+            r"^every\s+(?P<n>\d+)\s+hours?(?:\s+at\s+:(?P<m>\d{{1,2}}))?{OFFSET}$"
+        ))
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let amount = caps
+        .name("n")
+        .map(|m| m.as_str().parse::<u32>().unwrap_or(u32::MAX))
+        .filter(|&v| v > 0)
+        .unwrap_or(1);
+    let minute = match caps.name("m") {
+        Some(m) => parse_minute(input, m.as_str())?,
+        None => 0,
+    };
+    let offset = match (caps.name("how"), caps.name("offset")) {
+        (Some(how), Some(offset)) => match interval_offset(input, how.as_str(), offset.as_str())? {
+            Some(offset) => Some(offset),
+            None => return Ok(None),
+        },
+        _ => None,
+    };
+    if let Some(offset) = offset {
+        return offset_interval(amount.saturating_mul(60), "hour", offset + minute).map(Some);
+    }
+    if amount > 24 {
+        return Ok(Some(daily_restart(
+            amount.saturating_mul(60),
+            "hour",
+            minute,
+        )));
+    }
+    let hour = match amount {
+        1 => "*".to_string(),
+        24 => "0".to_string(),
+        _ => format!("*/{amount}"),
+    };
+    let mut explanation = if minute == 0 {
+        format!("Every {amount} hour(s)")
+    } else {
+        format!("Every {amount} hour(s) at :{:02}", minute)
+    };
+    if !24u32.is_multiple_of(amount) {
+        let times: Vec<String> = (0..24)
+            .step_by(amount as usize)
+            .map(|hour| format_clock(hour, minute))
+            .collect();
+        explanation = format!(
+            "{explanation}, starting over at midnight ({}; {} hours after the last run)",
+            join_and(&times),
+            24 - 23 / amount * amount
+        );
+    }
+    Ok(Some(CronSpec::new(
+        minute.to_string(),
+        hour,
+        "*",
+        "*",
+        "*",
+        explanation,
+    )))
+}
+
+/// An interval of `step` minutes, longer than a day, as cron runs it: once
+/// a day, `at` minutes after midnight, with a warning saying so.
+fn daily_restart(step: u32, unit: &str, at: u32) -> CronSpec {
+    let amount = if unit == "hour" { step / 60 } else { step };
+    let clock = format_clock(at / 60, at % 60);
+    CronSpec {
+        warnings: vec![format!(
+            "Every {amount} {unit}s is longer than a day, and cron starts its schedule over at midnight, so this runs once a day at {clock}"
+        )],
+        ..CronSpec::new(
+            (at % 60).to_string(),
+            (at / 60).to_string(),
+            "*",
+            "*",
+            "*",
+            format!(
+                "Every {amount} {unit}(s), which cron starts over at midnight, so once a day at {clock}"
+            ),
+        )
+    }
+}
+
+/// The offset modifier after an interval, as in `starting at :05`, `from
+/// 01:00` or `offset by 5 minutes`.
+const OFFSET: &str =
+    r"(?:\s+(?P<how>starting\s+(?:at|from)|starting|from|offset\s+by)\s+(?P<offset>.+))?";
+
+/// Minutes after midnight an interval's offset modifier puts a run at: a
+/// time of day, `:MM`, or with `offset by` an amount of minutes or hours.
+/// `Ok(None)` when `raw` is none of these.
+fn interval_offset(source: &str, how: &str, raw: &str) -> Result<Option<u32>, ParseError> {
+    static AMOUNT: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?P<n>\d+)\s*(?:(?P<hours>h|hours?)|m|mins?|minutes?)$").unwrap()
+    });
+    if let Some(minute) = raw.strip_prefix(':') {
+        return parse_minute(source, minute).map(Some);
+    }
+    if how == "offset by" {
+        let Some(caps) = AMOUNT.captures(raw) else {
+            return Ok(None);
+        };
+        let amount = caps["n"].parse::<u32>().unwrap_or(u32::MAX);
+        return Ok(Some(if caps.name("hours").is_some() {
+            amount.saturating_mul(60)
+        } else {
+            amount
+        }));
+    }
+    if !raw.starts_with(|c: char| c.is_ascii_digit()) && !["noon", "midnight"].contains(&raw) {
+        return Ok(None);
+    }
+    let (hour, minute) = parse_time_fragment(source, raw)?;
+    Ok(Some(hour * 60 + minute))
+}
+
+/// Every `step` minutes, phased so that a run falls `offset` minutes after
+/// midnight; `unit` is how the interval was given. The explanation lists the
+/// runs.
+fn offset_interval(step: u32, unit: &str, offset: u32) -> Result<CronSpec, ParseError> {
+    if step > MINUTES_PER_DAY {
+        return Ok(daily_restart(step, unit, offset % MINUTES_PER_DAY));
+    }
+    let phase = offset % step;
+    let amount = if unit == "hour" { step / 60 } else { step };
+    // Intervals under an hour that don't divide the day start over every
+    // hour, as a plain `*/N` would, unless that leaves one run an hour; those
+    // are spelled out over the day instead.
+    let hourly = step < 60
+        && (60u32.is_multiple_of(step)
+            || (!MINUTES_PER_DAY.is_multiple_of(step) && phase + step < 60));
+    let period = if hourly { 60 } else { MINUTES_PER_DAY };
+    let runs: Vec<u32> = (phase..period).step_by(step as usize).collect();
+    let clocks: Vec<String> = runs
+        .iter()
+        .map(|run| match hourly {
+            true => format!(":{run:02}"),
+            false => format_clock(run / 60, run % 60),
+        })
+        .collect();
+    let at = match clocks.as_slice() {
+        [first, .., last] if clocks.len() > 12 => format!("from {first} to {last}"),
+        _ => format!("at {}", join_and(&clocks)),
+    };
+    let restart = if hourly && 60u32.is_multiple_of(step) {
+        " past each hour"
+    } else if hourly {
+        ", starting over every hour"
+    } else if !MINUTES_PER_DAY.is_multiple_of(step) {
+        ", starting over at midnight"
+    } else {
+        ""
+    };
+    let explanation = format!("Every {amount} {unit}(s) {at}{restart}");
+
+    let stepped = |start: u32, max: u32, every: u32| match start {
+        _ if every == 1 => "*".to_string(),
+        _ if start + every > max => start.to_string(),
+        0 => format!("*/{every}"),
+        _ => format!("{start}-{max}/{every}"),
+    };
+    let lines = if hourly {
+        vec![CronSpec::new(
+            stepped(phase, 59, step),
+            "*",
+            "*",
+            "*",
+            "*",
+            explanation.clone(),
+        )]
+    } else if step.is_multiple_of(60) {
+        vec![CronSpec::new(
+            (phase % 60).to_string(),
+            stepped(phase / 60, 23, step / 60),
+            "*",
+            "*",
+            "*",
+            explanation.clone(),
+        )]
+    } else {
+        run_lines(&runs, None)?
+    };
+    Ok(CronSpec::from_lines(lines, explanation))
+}
+
+fn try_parse_daily(input: &str) -> Attempt {
+    static RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?:(?:every\s+)?day|daily)(?:\s+at\s+)?(?P<time>.+)$").unwrap());
+    let Some(time) = RE.captures(input).and_then(|caps| caps.name("time")) else {
+        return Ok(None);
+    };
+    let times = parse_time_list(input, time.as_str())?;
+    Ok(Some(at_times(&times, ["*", "*", "*"], |times| {
+        format!("Daily at {times}")
+    })))
+}
+
+fn try_parse_weekdayish(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?:(?:every\s+)?(?P<kind>weekdays?|weekends?))\s+(?:at\s+)?(?P<time>.+)$")
+            .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let times = parse_time_list(input, &caps["time"])?;
+    let (dow, label) = if caps["kind"].starts_with("weekend") {
+        ("6,0", "Weekends")
+    } else {
+        ("1-5", "Weekdays")
+    };
+    Ok(Some(at_times(&times, ["*", "*", dow], |times| {
+        format!("{label} at {times}")
+    })))
+}
+
+fn try_parse_specific_days(input: &str) -> Attempt {
+    let Some((prefix, time_part)) = input.split_once(" at ") else {
+        return Ok(None);
+    };
+    let Some(dow_set) = parse_day_list(input, prefix)? else {
+        return Ok(None);
+    };
+    let times = parse_time_list(input, time_part)?;
+    let days = describe_days(&dow_set.days);
+    Ok(Some(at_times(
+        &times,
+        ["*", "*", &dow_set.cron_value],
+        |times| format!("{days} at {times}"),
+    )))
+}
+
+fn try_parse_monthly(input: &str) -> Attempt {
+    let Some(remainder) = input.strip_prefix("monthly") else {
+        return Ok(None);
+    };
+    let remainder = remainder.trim();
+    if remainder.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(rest) = remainder.strip_prefix("on ") {
+        let Some((dom_part, time_part)) = rest.split_once(" at ") else {
+            return Ok(None);
+        };
+        let Some(dom) = parse_dom_list(input, dom_part)? else {
+            return Ok(None);
+        };
+        let times = parse_time_list(input, time_part)?;
+        return Ok(Some(at_times(
+            &times,
+            [&dom.cron_value, "*", "*"],
+            |times| format!("Monthly on {} at {times}", dom.human_value),
+        )));
+    }
+
+    if let Some(time_part) = remainder.strip_prefix("at ") {
+        let times = parse_time_list(input, time_part)?;
+        return Ok(Some(at_times(&times, ["1", "*", "*"], |times| {
+            format!("Monthly on day 1 at {times} (default day)")
+        })));
+    }
+
+    Ok(None)
+}
+
+fn try_parse_on_days(input: &str) -> Attempt {
+    let Some(remainder) = input.strip_prefix("on ") else {
+        return Ok(None);
+    };
+    let Some((dom_part, time_part)) = remainder.trim().split_once(" at ") else {
+        return Ok(None);
+    };
+    let Some(dom) = parse_dom_list(input, dom_part)? else {
+        return Ok(None);
+    };
+    let times = parse_time_list(input, time_part)?;
+    Ok(Some(at_times(
+        &times,
+        [&dom.cron_value, "*", "*"],
+        |times| format!("On {} at {times}", dom.human_value),
+    )))
+}
+
+/// `last day of the month at 23:00`, `2 days before month end`, `the second
+/// to last day of each month`, `monthly on the last day`.
+///
+/// The day goes into day-of-month as Quartz's `L` or `L-n`; Vixie cron needs
+/// a [`day_guard`](crate::day_guard) to run it.
+fn try_parse_month_end(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        let end = r"(?:last\s+day|end\s+of\s+(?:the\s+)?month|month[\s-]end)";
+        Regex::new(&format!(
+            r"^(?:(?:monthly|every\s+month|each\s+month)\s+)?(?:(?:on|at)\s+)?(?:the\s+)?(?:(?P<count>\d+|an?|one|two|three|four|five|six|seven)\s+days?\s+before\s+(?:the\s+)?{end}|(?P<before>day)\s+before\s+(?:the\s+)?{end}|(?P<place>second|2nd|third|3rd)[\s-]+(?:to[\s-]+)?last\s+day|{end})(?:\s+of\s+(?:the|each|every)\s+month|\s+(?:of\s+)?(?:each|every)\s+month|\s+of\s+(?P<months>[a-z][a-z ,&-]*?))?(?:\s+at\s+(?P<time>.+))?$"
+        ))
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let offset = match (caps.name("count"), caps.name("place")) {
+        (Some(count), _) => match count.as_str() {
+            "a" | "an" | "one" => 1,
+            "two" => 2,
+            "three" => 3,
+            "four" => 4,
+            "five" => 5,
+            "six" => 6,
+            "seven" => 7,
+            digits => {
+                let value = digits.parse::<u32>().unwrap_or(u32::MAX);
+                if value > 30 {
+                    return Err(ParseError::DayOfMonthOutOfRange {
+                        span: span_in(input, digits),
+                        value,
+                    });
+                }
+                value
+            }
+        },
+        (None, Some(place)) if matches!(place.as_str(), "second" | "2nd") => 1,
+        (None, Some(_)) => 2,
+        (None, None) if caps.name("before").is_some() => 1,
+        (None, None) => 0,
+    };
+    let months = match caps.name("months") {
+        Some(months) => match parse_month_list(input, months.as_str())? {
+            Some(months) => Some(months),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+
+    let day_of_month = match offset {
+        0 => "L".to_string(),
+        _ => format!("L-{offset}"),
+    };
+    let day = match offset {
+        0 => "the last day".to_string(),
+        1 => "the day before the last day".to_string(),
+        _ => format!("the day {offset} days before the last day"),
+    };
+    let of = match &months {
+        Some(months) => describe_months(&months.values),
+        None => "the month".to_string(),
+    };
+    let month = months.as_ref().map_or("*", |months| &months.cron_value);
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    Ok(Some(at_times(
+        &times,
+        [&day_of_month, month, "*"],
+        |times| format!("On {day} of {of} at {times}"),
+    )))
+}
+
+/// `first sunday of the month at 03:00`, `every second tuesday`, `the first
+/// and third monday of each month`, `last friday of march and september`.
+///
+/// The weekday goes into day-of-week as Quartz's `#` and `L` forms; Vixie
+/// cron needs a [`day_guard`](crate::day_guard) to run them.
+fn try_parse_ordinal_weekday(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        let nth = r"(?:first|1st|second|2nd|third|3rd|fourth|4th|fifth|5th|last)";
+        Regex::new(&format!(
+            r"^(?:on\s+)?(?:(?:the|every|each)\s+)?(?P<nth>{nth}(?:\s*(?:,|&|and|or)\s*(?:the\s+)?{nth})*)\s+(?P<day>[a-z]+)(?:\s+of\s+(?:the|each|every)\s+month|\s+(?:of\s+)?(?:each|every)\s+month|\s+monthly|\s+of\s+(?P<months>[a-z][a-z ,&-]*?))?(?:\s+at\s+(?P<time>.+))?$"
+        ))
+        .unwrap()
+    });
+    static NTH: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"first|1st|second|2nd|third|3rd|fourth|4th|fifth|5th|last").unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let token = caps.name("day").map_or("", |m| m.as_str());
+    let Some(day) = day_number(token).or_else(|| day_number(token.strip_suffix('s')?)) else {
+        return match suggest(token, WEEKDAY_NAMES) {
+            Some(suggestion) => Err(ParseError::UnknownWeekday {
+                span: span_in(input, token),
+                token: token.to_string(),
+                suggestion: Some(suggestion),
+            }),
+            None => Ok(None),
+        };
+    };
+    // Places in the month, with 6 for the last.
+    let mut places: Vec<usize> = NTH
+        .find_iter(&caps["nth"])
+        .map(|place| match place.as_str() {
+            "first" | "1st" => 1,
+            "second" | "2nd" => 2,
+            "third" | "3rd" => 3,
+            "fourth" | "4th" => 4,
+            "fifth" | "5th" => 5,
+            _ => 6,
+        })
+        .collect();
+    places.sort_unstable();
+    places.dedup();
+    let months = match caps.name("months") {
+        Some(months) => match parse_month_list(input, months.as_str())? {
+            Some(months) => Some(months),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+
+    let day_of_week = places
+        .iter()
+        .map(|place| match place {
+            6 => format!("{day}L"),
+            _ => format!("{day}#{place}"),
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let ordinals: Vec<String> = places
+        .iter()
+        .map(|place| ["first", "second", "third", "fourth", "fifth", "last"][place - 1].to_string())
+        .collect();
+    let name = capitalize(WEEKDAY_NAMES[usize::from(day)]);
+    let of = match &months {
+        Some(months) => describe_months(&months.values),
+        None => "the month".to_string(),
+    };
+    let month = months.as_ref().map_or("*", |months| &months.cron_value);
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    Ok(Some(at_times(
+        &times,
+        ["*", month, &day_of_week],
+        |times| format!("On the {} {name} of {of} at {times}", join_and(&ordinals)),
+    )))
+}
+
+/// `yearly on dec 25`, `annually on the 3rd of march at 06:00`.
+fn try_parse_yearly_on(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^(?:yearly|annually|every\s+year)\s+on\s+(?:the\s+)?(?P<date>.+?)(?:\s+at\s+(?P<time>.+))?$",
+        )
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let Some((month, day)) = parse_month_day(input, &caps["date"])? else {
+        return Ok(None);
+    };
+    running_months(span_of(input, &caps, "date"), &[day], &[month])?;
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    let month_name = describe_months(&[month]);
+    Ok(Some(at_times(
+        &times,
+        [&day.to_string(), &month.to_string(), "*"],
+        |times| format!("Yearly on {month_name} {day} at {times}"),
+    )))
+}
+
+/// `quarterly`, `semiannually` or `twice a year`, on the 1st unless another
+/// date is given.
+fn try_parse_period_of_year(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^(?P<period>quarterly|every\s+quarter|semi-?annually|twice\s+a\s+year|every\s+(?:6|six)\s+months)(?:\s+on\s+(?:the\s+)?(?P<dom>.+?))?(?:\s+at\s+(?P<time>.+))?$",
+        )
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let (months, label): (&[u32], _) = if caps["period"].contains("quarter") {
+        (&[1, 4, 7, 10], "Quarterly")
+    } else {
+        (&[1, 7], "Twice a year")
+    };
+    let dom = match caps.name("dom") {
+        Some(dom) => match parse_dom_list(input, dom.as_str())? {
+            Some(dom) => dom,
+            None => return Ok(None),
+        },
+        None => DomList {
+            cron_value: "1".to_string(),
+            human_value: "1".to_string(),
+            values: vec![1],
+        },
+    };
+    let runs = running_months(span_of(input, &caps, "dom"), &dom.values, months)?;
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    let month_names = describe_months(&runs);
+    Ok(Some(at_times(
+        &times,
+        [&dom.cron_value, &compress(FieldKind::Month, months), "*"],
+        |times| {
+            format!(
+                "{label} on day {} at {times} ({month_names})",
+                dom.human_value
+            )
+        },
+    )))
+}
+
+/// `on the 1st of january and july`, `on the 15th of jun-aug at 09:00`.
+fn try_parse_of_months(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^on\s+(?:the\s+)?(?P<dom>.+?)\s+of\s+(?P<months>.+?)(?:\s+at\s+(?P<time>.+))?$",
+        )
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let Some(dom) = parse_dom_list(input, &caps["dom"])? else {
+        return Ok(None);
+    };
+    let Some(months) = parse_month_list(input, caps.name("months").map_or("", |m| m.as_str()))?
+    else {
+        return Ok(None);
+    };
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    months_on(span_of(input, &caps, "dom"), &dom, &months, &times).map(Some)
+}
+
+/// `every january`, `every march and september on the 15th at 06:00`.
+fn try_parse_every_month_name(input: &str) -> Attempt {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^(?:every|each)\s+(?P<months>.+?)(?:\s+on\s+(?:the\s+)?(?P<dom>.+?))?(?:\s+at\s+(?P<time>.+))?$",
+        )
+        .unwrap()
+    });
+    let Some(caps) = RE.captures(input) else {
+        return Ok(None);
+    };
+    let Some(months) = parse_month_list(input, caps.name("months").map_or("", |m| m.as_str()))?
+    else {
+        return Ok(None);
+    };
+    let dom = match caps.name("dom") {
+        Some(dom) => match parse_dom_list(input, dom.as_str())? {
+            Some(dom) => dom,
+            None => return Ok(None),
+        },
+        None => DomList {
+            cron_value: "1".to_string(),
+            human_value: "1".to_string(),
+            values: vec![1],
+        },
+    };
+    let times = optional_times(input, caps.name("time").map(|m| m.as_str()))?;
+    months_on(span_of(input, &caps, "dom"), &dom, &months, &times).map(Some)
+}
+
+/// The schedule for `times` on the `dom` days of `months`, given at `span`.
+/// The explanation names only the months that have one of the days.
+fn months_on(
+    span: Range<usize>,
+    dom: &DomList,
+    months: &MonthList,
+    times: &[(u32, u32)],
+) -> Result<CronSpec, ParseError> {
+    let days = if dom.cron_value.contains(',') {
+        "days"
+    } else {
+        "day"
+    };
+    let month_names = describe_months(&running_months(span, &dom.values, &months.values)?);
+    Ok(at_times(
+        times,
+        [&dom.cron_value, &months.cron_value, "*"],
+        |times| format!("On {days} {} of {month_names} at {times}", dom.human_value),
+    ))
+}
+
+/// Those of `months` that have at least one of `days`, or an error at `span`
+/// for a day none of them has, such as February 30.
+fn running_months(
+    span: Range<usize>,
+    days: &[u32],
+    months: &[u32],
+) -> Result<Vec<u32>, ParseError> {
+    if let Some(&day) = days
+        .iter()
+        .find(|&&day| months.iter().all(|&month| month_length(month) < day))
+    {
+        return Err(ParseError::NoSuchDate {
+            span,
+            day,
+            months: describe_months(months),
+        });
+    }
+    Ok(months
+        .iter()
+        .copied()
+        .filter(|&month| days.iter().any(|&day| day <= month_length(month)))
+        .collect())
+}
+
+/// The span of the named group, or of all of `input` when it didn't match.
+fn span_of(input: &str, caps: &Captures, name: &str) -> Range<usize> {
+    caps.name(name).map_or(0..input.len(), |m| m.range())
+}
+
+/// The times after `at`, or midnight when there are none.
+fn optional_times(source: &str, raw: Option<&str>) -> Result<Vec<(u32, u32)>, ParseError> {
+    match raw {
+        Some(raw) => parse_time_list(source, raw),
+        None => Ok(vec![(0, 0)]),
+    }
+}
+
+/// Split a list such as `mon, wed & fri` into its words, dropping the
+/// separators and the word `and`.
+fn list_tokens(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(|c: char| c == ',' || c == '&' || c.is_whitespace())
+        .filter(|token| !token.is_empty() && *token != "and")
+}
+
+struct DayList {
+    cron_value: String,
+    days: Vec<u8>,
+}
+
+/// Parse a list of weekday names. Returns `Ok(None)` when the text does not
+/// look like a day list at all, and an error for a word that is close to,
+/// but not quite, a weekday.
+fn parse_day_list(source: &str, prefix: &str) -> Result<Option<DayList>, ParseError> {
+    let stop_words = ["every", "each", "on", "week", "weeks", "weekly", "the"];
+    let mut days = Vec::new();
+    for token in list_tokens(prefix) {
+        if stop_words.contains(&token) {
+            continue;
+        }
+        let cleaned = token.strip_suffix('s').unwrap_or(token);
+        if let Some(value) = day_number(cleaned) {
+            if !days.contains(&value) {
+                days.push(value);
+            }
+            continue;
+        }
+        if !token.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Ok(None);
+        }
+        return match suggest(cleaned, WEEKDAY_NAMES) {
+            Some(suggestion) => Err(ParseError::UnknownWeekday {
+                span: span_in(source, token),
+                token: token.to_string(),
+                suggestion: Some(suggestion),
+            }),
+            None => Ok(None),
+        };
+    }
+    if days.is_empty() {
+        return Ok(None);
+    }
+    days.sort();
+    let cron_value = days
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Some(DayList { cron_value, days }))
+}
+
+const WEEKDAY_NAMES: &[&str] = &[
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+struct MonthList {
+    cron_value: String,
+    values: Vec<u32>,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Parse month names, ranges and seasons, as in `jan and jul`, `jun-aug`,
+/// `june through august` or `summer months`. Returns `Ok(None)` when the text
+/// is not a month list, and an error for a word that is close to, but not
+/// quite, a month.
+fn parse_month_list(source: &str, raw: &str) -> Result<Option<MonthList>, ParseError> {
+    static RANGE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?P<from>[a-z]+)\.?\s*(?:-|to|through|thru|until)\s*(?P<to>[a-z]+)\.?$")
+            .unwrap()
+    });
+    let mut values = Vec::new();
+    for part in raw.split([',', '&']).flat_map(|part| part.split(" and ")) {
+        let part = part.trim();
+        let part = part
+            .strip_suffix(" months")
+            .or_else(|| part.strip_suffix(" month"))
+            .unwrap_or(part);
+        if part.is_empty() {
+            continue;
+        }
+        let season: &[u32] = match part {
+            "spring" => &[3, 4, 5],
+            "summer" => &[6, 7, 8],
+            "autumn" | "fall" => &[9, 10, 11],
+            "winter" => &[12, 1, 2],
+            _ => &[],
+        };
+        if !season.is_empty() {
+            values.extend(season);
+            continue;
+        }
+        let (from, to) = match RANGE.captures(part) {
+            Some(caps) => {
+                let (from, to) = (caps.name("from"), caps.name("to"));
+                (
+                    from.map_or("", |m| m.as_str()),
+                    to.map_or("", |m| m.as_str()),
+                )
+            }
+            None => (part.trim_end_matches('.'), part.trim_end_matches('.')),
+        };
+        let mut ends = [0; 2];
+        for (end, token) in ends.iter_mut().zip([from, to]) {
+            if let Some(month) = month_number(token) {
+                *end = u32::from(month);
+                continue;
+            }
+            if !token.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Ok(None);
+            }
+            return match suggest(token, MONTH_NAMES) {
+                Some(suggestion) => Err(ParseError::UnknownMonth {
+                    span: span_in(source, token),
+                    token: token.to_string(),
+                    suggestion: Some(suggestion),
+                }),
+                None => Ok(None),
+            };
+        }
+        let [from, to] = ends;
+        // A range such as `nov-feb` runs through the end of the year.
+        let mut month = from;
+        loop {
+            values.push(month);
+            if month == to {
+                break;
+            }
+            month = month % 12 + 1;
+        }
+    }
+    if values.is_empty() {
+        return Ok(None);
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(Some(MonthList {
+        cron_value: compress(FieldKind::Month, &values),
+        values,
+    }))
+}
+
+/// A month and day in either order, as in `dec 25`, `march 3rd` or `the 1st
+/// of january`.
+fn parse_month_day(source: &str, raw: &str) -> Result<Option<(u32, u32)>, ParseError> {
+    let tokens: Vec<&str> = list_tokens(raw)
+        .filter(|token| !["the", "of"].contains(token))
+        .collect();
+    let [first, second] = tokens[..] else {
+        return Ok(None);
+    };
+    let (month, day) = match (month_number(first), month_number(second)) {
+        (Some(month), None) => (month, second),
+        (None, Some(month)) => (month, first),
+        (None, None) => {
+            let word = [first, second]
+                .into_iter()
+                .find(|token| token.chars().all(|c| c.is_ascii_alphabetic()));
+            return match word.and_then(|word| Some((word, suggest(word, MONTH_NAMES)?))) {
+                Some((word, suggestion)) => Err(ParseError::UnknownMonth {
+                    span: span_in(source, word),
+                    token: word.to_string(),
+                    suggestion: Some(suggestion),
+                }),
+                None => Ok(None),
+            };
+        }
+        _ => return Ok(None),
+    };
+    let Some(day) = parse_dom_list(source, day)? else {
+        return Ok(None);
+    };
+    Ok(day
+        .cron_value
+        .parse()
+        .ok()
+        .map(|day| (u32::from(month), day)))
+}
+
+fn month_number(token: &str) -> Option<u8> {
+    match token.trim_end_matches('.') {
+        "jan" | "january" => Some(1),
+        "feb" | "february" => Some(2),
+        "mar" | "march" => Some(3),
+        "apr" | "april" => Some(4),
+        "may" => Some(5),
+        "jun" | "june" => Some(6),
+        "jul" | "july" => Some(7),
+        "aug" | "august" => Some(8),
+        "sep" | "sept" | "september" => Some(9),
+        "oct" | "october" => Some(10),
+        "nov" | "november" => Some(11),
+        "dec" | "december" => Some(12),
+        _ => None,
+    }
+}
+
+fn day_number(token: &str) -> Option<u8> {
+    match token {
+        "sun" | "sunday" => Some(0),
+        "mon" | "monday" => Some(1),
+        "tue" | "tues" | "tuesday" => Some(2),
+        "wed" | "weds" | "wednesday" => Some(3),
+        "thu" | "thur" | "thurs" | "thursday" => Some(4),
+        "fri" | "friday" => Some(5),
+        "sat" | "saturday" => Some(6),
+        _ => None,
+    }
+}
+
+/// The candidate closest to `token`, if it is within a couple of edits.
+fn suggest(token: &str, candidates: &[&'static str]) -> Option<&'static str> {
+    if token.len() < 3 {
+        return None;
+    }
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(token, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct DomList {
+    cron_value: String,
+    human_value: String,
+    values: Vec<u32>,
+}
+
+fn parse_dom_list(source: &str, raw: &str) -> Result<Option<DomList>, ParseError> {
+    let mut values = Vec::new();
+    for token in list_tokens(raw) {
+        let digits = token.trim_end_matches(|c: char| !c.is_ascii_digit());
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let value = digits.parse::<u32>().unwrap_or(u32::MAX);
+        if !(1..=31).contains(&value) {
+            return Err(ParseError::DayOfMonthOutOfRange {
+                span: span_in(source, digits),
+                value,
+            });
+        }
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        return Ok(None);
+    }
+    values.sort();
+    let cron_value = values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let human_value = values
+        .iter()
+        .map(|v| format!("{v}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(Some(DomList {
+        cron_value,
+        human_value,
+        values,
+    }))
+}
+
+/// Parse one or more times, as in `9am and 5pm` or `09:00, 12:00 & 17:30`,
+/// into (hour, minute) pairs in order.
+fn parse_time_list(source: &str, raw: &str) -> Result<Vec<(u32, u32)>, ParseError> {
+    let mut times = Vec::new();
+    for part in raw.split([',', '&']).flat_map(|part| part.split(" and ")) {
+        if part.trim().is_empty() {
+            continue;
+        }
+        let time = parse_time_fragment(source, part)?;
+        if !times.contains(&time) {
+            times.push(time);
+        }
+    }
+    if times.is_empty() {
+        times.push(parse_time_fragment(source, raw)?);
+    }
+    times.sort_unstable();
+    Ok(times)
+}
+
+/// The schedule for `times` on the given day-of-month, month and day-of-week.
+///
+/// Hours that run at the same minutes share a cron line, so `09:00 and 17:00`
+/// is `0 9,17`; `09:00 and 17:30` takes a line each. `describe` turns a list
+/// of times such as `09:00 and 17:30` into an explanation.
+fn at_times(times: &[(u32, u32)], days: [&str; 3], describe: impl Fn(&str) -> String) -> CronSpec {
+    let clocks = |times: &mut dyn Iterator<Item = (u32, u32)>| {
+        join_and(
+            &times
+                .map(|(hour, minute)| format_clock(hour, minute))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let mut by_hour: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for &(hour, minute) in times {
+        by_hour.entry(hour).or_default().push(minute);
+    }
+    let mut groups: Vec<(Vec<u32>, Vec<u32>)> = Vec::new();
+    for (hour, minutes) in by_hour {
+        match groups.iter_mut().find(|(shared, _)| *shared == minutes) {
+            Some((_, hours)) => hours.push(hour),
+            None => groups.push((minutes, vec![hour])),
+        }
+    }
+    let [day_of_month, month, day_of_week] = days;
+    let list = |values: &[u32]| {
+        values
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let lines = groups
+        .iter()
+        .map(|(minutes, hours)| {
+            let mut line_times = hours
+                .iter()
+                .flat_map(|hour| minutes.iter().map(move |minute| (*hour, *minute)));
+            CronSpec::new(
+                list(minutes),
+                list(hours),
+                day_of_month,
+                month,
+                day_of_week,
+                describe(&clocks(&mut line_times)),
+            )
+        })
+        .collect();
+    CronSpec::from_lines(lines, describe(&clocks(&mut times.iter().copied())))
+}
+
+/// Parse `HH:MM`, `H`, `Ham`, `H:MMpm`, `noon` or `midnight` into (hour, minute).
+fn parse_time_fragment(source: &str, raw: &str) -> Result<(u32, u32), ParseError> {
+    let trimmed = raw.trim();
+    if trimmed == "midnight" {
+        return Ok((0, 0));
+    }
+    if trimmed == "noon" {
+        return Ok((12, 0));
+    }
+    let bad_time = || ParseError::BadTime {
+        span: span_in(source, trimmed),
+        text: trimmed.to_string(),
+    };
+
+    let mut fragment = trimmed;
+    let mut meridian = None;
+    if let Some(rest) = fragment.strip_suffix("am") {
+        fragment = rest.trim_end();
+        meridian = Some("am");
+    } else if let Some(rest) = fragment.strip_suffix("pm") {
+        fragment = rest.trim_end();
+        meridian = Some("pm");
+    }
+
+    let (hour_part, minute_part) = match fragment.split_once(':') {
+        Some((hour, minute)) => (hour.trim(), Some(minute.trim())),
+        None => (fragment, None),
+    };
+    let hour = parse_number(hour_part).ok_or_else(bad_time)?;
+    let max_hour = if meridian.is_some() { 12 } else { 23 };
+    if hour > max_hour {
+        return Err(ParseError::HourOutOfRange {
+            span: span_in(source, hour_part),
+            value: hour,
+            max_label: if meridian.is_some() { "1-12" } else { "0-23" },
+        });
+    }
+    let minute = match minute_part {
+        Some(value) => {
+            parse_number(value).ok_or_else(bad_time)?;
+            parse_minute(source, value)?
+        }
+        None => 0,
+    };
+
+    let mut hour = hour;
+    if let Some(marker) = meridian {
+        if marker == "am" {
+            if hour == 12 {
+                hour = 0;
+            }
+        } else if hour != 12 {
+            hour += 12;
+        }
+    }
+
+    Ok((hour, minute))
+}
+
+fn parse_number(raw: &str) -> Option<u32> {
+    if raw.is_empty() || !raw.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(raw.parse::<u32>().unwrap_or(u32::MAX))
+}
+
+/// Parse a bare minute value, rejecting anything past 59.
+fn parse_minute(source: &str, raw: &str) -> Result<u32, ParseError> {
+    let value = parse_number(raw).unwrap_or(u32::MAX);
+    if value > 59 {
+        return Err(ParseError::MinuteOutOfRange {
+            span: span_in(source, raw),
+            value,
+        });
+    }
+    Ok(value)
+}
+
+fn format_clock(hour: u32, minute: u32) -> String {
+    format!("{:02}:{:02}", hour, minute)
+}
+
+fn describe_days(days: &[u8]) -> String {
+    let labels = days
+        .iter()
+        .map(|d| match d {
+            0 => "Sundays",
+            1 => "Mondays",
+            2 => "Tuesdays",
+            3 => "Wednesdays",
+            4 => "Thursdays",
+            5 => "Fridays",
+            _ => "Saturdays",
+        })
+        .collect::<Vec<_>>();
+    if labels.len() == 1 {
+        labels[0].to_string()
+    } else {
+        labels.join(", ")
+    }
+}
+
+pub(crate) fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::spec_fields;
+
+    /// Minutes after midnight of every run in a day, in order.
+    fn run_times(expression: &str) -> Vec<u32> {
+        let mut runs = Vec::new();
+        for line in parse_expression(expression).unwrap().lines() {
+            let [minute, hour, ..] = spec_fields(&line).unwrap();
+            let minute = minute.set();
+            for h in hour.set().values() {
+                runs.extend(minute.values().map(|m| h * 60 + m));
+            }
+        }
+        runs.sort_unstable();
+        runs
+    }
+
+    fn lines(expression: &str) -> Vec<String> {
+        parse_expression(expression)
+            .unwrap()
+            .lines()
+            .iter()
+            .map(CronSpec::as_string)
+            .collect()
+    }
+
+    #[test]
+    fn intervals_longer_than_a_day_run_once_a_day() {
+        for (expression, cron) in [
+            ("every 36 hours", "0 0 * * *"),
+            ("every 48 hours at :30", "30 0 * * *"),
+            ("every 1500 minutes", "0 0 * * *"),
+            ("every 2000 minutes", "0 0 * * *"),
+            ("every 36 hours starting at 12:00", "0 12 * * *"),
+            ("every 25 hours offset by 30 hours", "0 6 * * *"),
+        ] {
+            let spec = parse_expression(expression).unwrap();
+            assert_eq!(lines(expression), [cron], "{expression}");
+            assert_eq!(spec.warnings.len(), 1, "{expression}");
+            assert!(spec.warnings[0].contains("runs once a day"), "{expression}");
+        }
+        assert!(
+            parse_expression("every 24 hours")
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn intervals_that_divide_the_day_are_spelled_out() {
+        assert_eq!(
+            lines("every 90 minutes"),
+            ["0 */3 * * *", "30 1-22/3 * * *"]
+        );
+        assert_eq!(
+            lines("every 45 minutes"),
+            ["0,45 */3 * * *", "30 1-22/3 * * *", "15 2-23/3 * * *"]
+        );
+        assert_eq!(lines("every 7 minutes"), ["*/7 * * * *"]);
+        assert_eq!(lines("every 5 hours"), ["0 */5 * * *"]);
+    }
+
+    #[test]
+    fn offsets_that_leave_one_run_an_hour_are_spelled_out() {
+        for expression in [
+            "every 50 minutes starting at :30",
+            "every 50 minutes offset by 2 hours",
+        ] {
+            let runs = run_times(expression);
+            assert_eq!(runs.len(), 29, "{expression}");
+            assert!(
+                runs.windows(2).all(|pair| pair[1] - pair[0] == 50),
+                "{expression}: {runs:?}"
+            );
+        }
+        assert_eq!(lines("every 7 minutes starting at :03"), ["3-59/7 * * * *"]);
+        assert_eq!(
+            lines("every 15 minutes offset by 5 minutes"),
+            ["5-59/15 * * * *"]
+        );
+    }
+
+    #[test]
+    fn windows_keep_their_interval_and_may_cross_midnight() {
+        assert_eq!(
+            lines("every 15 minutes between 9am and 5pm on weekdays"),
+            ["*/15 9-16 * * 1-5"]
+        );
+        assert_eq!(
+            lines("every 30 minutes between 22:00 and 02:00"),
+            ["0,30 22,23 * * *", "0,30 0,1 * * *"]
+        );
+    }
+
+    #[test]
+    fn several_times_share_a_line_when_their_minutes_match() {
+        assert_eq!(lines("daily at 9am and 5pm"), ["0 9,17 * * *"]);
+        assert_eq!(
+            lines("daily at 09:00 and 17:30"),
+            ["0 9 * * *", "30 17 * * *"]
+        );
+    }
+
+    #[test]
+    fn month_qualifiers_restrict_the_month_field() {
+        assert_eq!(lines("on the 1st of January and July"), ["0 0 1 1,7 *"]);
+        assert_eq!(lines("quarterly"), ["0 0 1 */3 *"]);
+        assert_eq!(lines("yearly on Dec 25"), ["0 0 25 12 *"]);
+        assert_eq!(
+            parse_expression("on the 31st of april"),
+            Err(ParseError::NoSuchDate {
+                span: 7..11,
+                day: 31,
+                months: "April".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn ordinal_weekdays_and_month_ends_use_quartz_days() {
+        let day_of_week = |expression| parse_expression(expression).unwrap().day_of_week;
+        assert_eq!(day_of_week("first monday of the month at 09:00"), "1#1");
+        assert_eq!(day_of_week("every second tuesday"), "2#2");
+        assert_eq!(day_of_week("last friday of the month"), "5L");
+        let day_of_month = |expression| parse_expression(expression).unwrap().day_of_month;
+        assert_eq!(day_of_month("last day of the month"), "L");
+        assert_eq!(
+            day_of_month("2 days before the end of the month at 06:00"),
+            "L-2"
+        );
+    }
+
+    #[test]
+    fn macros_keep_their_name_and_fields() {
+        let weekly = parse_expression("@weekly").unwrap();
+        assert_eq!(weekly.as_string(), "@weekly");
+        assert_eq!(weekly.five_fields(), "0 0 * * 0");
+        assert!(parse_expression("@reboot").unwrap().is_reboot());
+        assert!(matches!(
+            parse_expression("@fortnightly"),
+            Err(ParseError::UnknownMacro { .. })
+        ));
+    }
+
+    #[test]
+    fn mistakes_point_at_the_word_and_suggest_a_fix() {
+        assert_eq!(
+            parse_expression("mondya at 09:00"),
+            Err(ParseError::UnknownWeekday {
+                span: 0..6,
+                token: "mondya".to_string(),
+                suggestion: Some("monday"),
+            })
+        );
+        let err = parse_expression("every monday at 25:00").unwrap_err();
+        assert_eq!(err.span(), Some(16..18));
+        assert!(matches!(
+            parse_expression("daily at 9:00 Europe/Berlni"),
+            Err(ParseError::UnknownTimeZone { name, .. }) if name == "Europe/Berlni"
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::crontab::{CRON_TZ, ID_MARKER, ZONE_VARIABLES};
+use crate::error::Result;
+use crate::guard::day_guard;
+use crate::spec::{CronEntry, CronSpec, EnvVar};
+use crate::store::{Backend, Backups, edit_crontab};
+use crate::zone::host_zone;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Guess the current user's cron file.
+///
+/// `$CRONTAB` wins when set; otherwise the usual spool and `cron.d` locations
+/// are probed before falling back to [`default_cron_file`].
+pub fn detect_cron_file() -> PathBuf {
+    if let Ok(path) = env::var("CRONTAB") {
+        return PathBuf::from(path);
+    }
+
+    let username = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "user".to_string());
+
+    let candidates = [
+        format!("/var/spool/cron/crontabs/{username}"),
+        format!("/var/spool/cron/{username}"),
+        format!("/etc/cron.d/{username}"),
+    ];
+
+    for candidate in candidates {
+        let path = PathBuf::from(&candidate);
+        if path.exists() || path.parent().is_some_and(Path::exists) {
+            return path;
+        }
+    }
+
+    default_cron_file()
+}
+
+/// `~/.crontab`, or `./.crontab` when no home directory is known.
+pub fn default_cron_file() -> PathBuf {
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."));
+    home.join(".crontab")
+}
+
+/// Render an entry as the block of lines that goes into a crontab.
+///
+/// The block opens with a `# cronoisseur:id=<id>` marker so the entry can be
+/// found again by [`replace_entry`](crate::replace_entry) and
+/// [`remove_entry`](crate::remove_entry). Each line of the schedule gets a
+/// job line running the command. A schedule meant in another zone
+/// gets a `CRON_TZ=` line, which cronie evaluates it in. Like any variable
+/// it would also apply to the jobs below, so the block ends by setting
+/// `CRON_TZ`, and `TZ` when the entry sets it, back to the host's zone. See
+/// [`localize_entry`](crate::localize_entry) for the alternatives. Lines
+/// with weekdays picked by their place in the month, or days counted back
+/// from its end, run behind the check from [`day_guard`].
+pub fn render_entry(entry: &CronEntry) -> String {
+    render_block(entry, &[])
+}
+
+/// [`render_entry`] for a block below the assignments `in_force`, whose
+/// zone variables the block puts back rather than the host's zone.
+pub(crate) fn render_block(entry: &CronEntry, in_force: &[EnvVar]) -> String {
+    let mut lines = vec![format!("{ID_MARKER}{}", entry.managed_id())];
+    if let Some(comment) = &entry.comment {
+        lines.push(format!("# {comment}"));
+    }
+    for env in &entry.env {
+        lines.push(format!("{}={}", env.key, env.value));
+    }
+    if let Some(zone) = &entry.schedule.time_zone
+        && !entry.env.iter().any(|env| env.key == CRON_TZ)
+    {
+        lines.push(format!("{CRON_TZ}={zone}"));
+    }
+    let cron_tz = entry
+        .env
+        .iter()
+        .find(|env| env.key == CRON_TZ)
+        .map(|env| env.value.clone());
+    for line in entry.schedule.lines() {
+        // The check reads the date in the zone cron evaluates the line in.
+        let zoned = CronSpec {
+            time_zone: line.time_zone.clone().or_else(|| cron_tz.clone()),
+            ..line.clone()
+        };
+        lines.push(match day_guard(&zoned) {
+            Some(guard) => format!(
+                "{} {} && {}",
+                guard.schedule.as_string(),
+                guard.check,
+                entry.command
+            ),
+            None => format!("{} {}", line.as_string(), entry.command),
+        });
+    }
+    for key in ZONE_VARIABLES {
+        let set = match key {
+            CRON_TZ => cron_tz.clone().or_else(|| entry.schedule.time_zone.clone()),
+            _ => entry
+                .env
+                .iter()
+                .find(|env| env.key == key)
+                .map(|env| env.value.clone()),
+        };
+        let Some(set) = set else {
+            continue;
+        };
+        let previous = match in_force.iter().rev().find(|env| env.key == key) {
+            Some(env) => env.value.clone(),
+            None => host_zone().name().to_string(),
+        };
+        if previous != set {
+            lines.push(format!("{key}={previous}"));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Append a rendered block to `path`, creating the file and its parent
+/// directories when needed.
+///
+/// The write goes through [`edit_crontab`] with the default [`Backups`], so
+/// it is locked, atomic and backed up like any other change.
+pub fn append_entry(path: &Path, block: &str) -> Result<()> {
+    edit_crontab(
+        &Backend::File(path.to_path_buf()),
+        &Backups::default(),
+        |existing| {
+            let mut contents = existing.to_string();
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(block);
+            contents.push('\n');
+            Ok((Some(contents), ()))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    fn entry(expression: &str) -> CronEntry {
+        CronEntry {
+            id: Some("report".to_string()),
+            schedule: parse_expression(expression).unwrap(),
+            command: "./report.sh".to_string(),
+            comment: Some("monthly report".to_string()),
+            env: vec![EnvVar {
+                key: "MAILTO".to_string(),
+                value: "ops".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn blocks_carry_marker_comment_and_environment() {
+        assert_eq!(
+            render_entry(&entry("first monday of the month at 09:00")),
+            "# cronoisseur:id=report\n# monthly report\nMAILTO=ops\n0 9 * * 1 [ \"$(date +\\%d)\" -le 7 ] && ./report.sh"
+        );
+        assert_eq!(
+            render_entry(&entry("daily at 09:00 and 17:30")),
+            "# cronoisseur:id=report\n# monthly report\nMAILTO=ops\n0 9 * * * ./report.sh\n30 17 * * * ./report.sh"
+        );
+    }
+
+    #[test]
+    fn zoned_blocks_check_the_date_in_their_zone_and_put_it_back() {
+        let in_force = [EnvVar {
+            key: CRON_TZ.to_string(),
+            value: "UTC".to_string(),
+        }];
+        assert_eq!(
+            render_block(
+                &entry("last day of the month at 23:00 Asia/Tokyo"),
+                &in_force
+            ),
+            "# cronoisseur:id=report\n# monthly report\nMAILTO=ops\nCRON_TZ=Asia/Tokyo\n0 23 28-31 * * [ \"$(TZ=Asia/Tokyo date -d tomorrow +\\%d)\" -eq 1 ] && ./report.sh\nCRON_TZ=UTC"
+        );
+        let same = [EnvVar {
+            key: CRON_TZ.to_string(),
+            value: "Asia/Tokyo".to_string(),
+        }];
+        assert_eq!(
+            render_block(&entry("daily at 09:00 Asia/Tokyo"), &same),
+            "# cronoisseur:id=report\n# monthly report\nMAILTO=ops\nCRON_TZ=Asia/Tokyo\n0 9 * * * ./report.sh"
+        );
+    }
+}
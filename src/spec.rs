@@ -0,0 +1,268 @@
+use crate::describe::describe_line;
+use chrono_tz::Tz;
+use serde::Serialize;
+
+/// A single `KEY=value` assignment placed above a cron entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+/// The five fields of a cron schedule plus a human-readable explanation.
+///
+/// Schedules read from Quartz or Spring expressions may also carry a seconds
+/// and a year field, and `L`, `W` or `#` in the day fields (see
+/// [`CronSpec::to_dialect`]). Day-of-week numbers are always Vixie's, with
+/// Sunday as 0.
+///
+/// A schedule written as a macro such as `@daily` keeps the macro alongside
+/// its five-field equivalent; `@reboot` has no fields at all.
+///
+/// `time_zone` names the IANA zone the fields are meant in, when that is not
+/// the zone of the machine running them.
+///
+/// Some schedules, such as `daily at 09:00 and 17:30`, take more than one
+/// cron line. The first is the schedule itself and the rest are in
+/// `extra_lines`; the explanation covers them all, and each line also
+/// carries a description of its own in `line_explanation`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CronSpec {
+    #[serde(rename = "macro", skip_serializing_if = "Option::is_none")]
+    pub macro_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub minute: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub hour: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub day_of_month: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub month: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub day_of_week: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_lines: Vec<CronSpec>,
+    pub explanation: String,
+    /// What this line alone does, when it is one of several.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_explanation: Option<String>,
+    /// What the phrase asked for that the fields can't show, such as an
+    /// interval cron starts over every day.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+impl CronSpec {
+    pub fn new(
+        minute: impl Into<String>,
+        hour: impl Into<String>,
+        day_of_month: impl Into<String>,
+        month: impl Into<String>,
+        day_of_week: impl Into<String>,
+        explanation: impl Into<String>,
+    ) -> Self {
+        Self {
+            macro_name: None,
+            second: None,
+            minute: minute.into(),
+            hour: hour.into(),
+            day_of_month: day_of_month.into(),
+            month: month.into(),
+            day_of_week: day_of_week.into(),
+            year: None,
+            time_zone: None,
+            extra_lines: Vec::new(),
+            explanation: explanation.into(),
+            line_explanation: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// `@reboot`: run once when the cron daemon starts rather than on a calendar.
+    pub fn reboot() -> Self {
+        Self {
+            macro_name: Some("@reboot".to_string()),
+            ..Self::new("", "", "", "", "", "At system startup")
+        }
+    }
+
+    pub fn is_reboot(&self) -> bool {
+        self.macro_name.as_deref() == Some("@reboot")
+    }
+
+    /// One schedule made of several cron lines, explained as a whole, with
+    /// each line described on its own as well.
+    ///
+    /// # Panics
+    ///
+    /// When `lines` is empty.
+    pub fn from_lines(lines: Vec<CronSpec>, explanation: impl Into<String>) -> Self {
+        let lines: Vec<CronSpec> = lines.into_iter().flat_map(|line| line.lines()).collect();
+        let several = lines.len() > 1;
+        let mut lines = lines.into_iter().map(|line| CronSpec {
+            line_explanation: several.then(|| {
+                describe_line(&line).unwrap_or_else(|| line.line_explanation().to_string())
+            }),
+            ..line
+        });
+        let first = lines.next().expect("a schedule has at least one line");
+        Self {
+            extra_lines: lines
+                .map(|line| CronSpec {
+                    time_zone: None,
+                    ..line
+                })
+                .collect(),
+            explanation: explanation.into(),
+            ..first
+        }
+    }
+
+    /// Every cron line of the schedule, each on its own and in its zone.
+    pub fn lines(&self) -> Vec<CronSpec> {
+        let first = CronSpec {
+            extra_lines: Vec::new(),
+            ..self.clone()
+        };
+        let mut lines = vec![first];
+        lines.extend(self.extra_lines.iter().map(|line| CronSpec {
+            time_zone: self.time_zone.clone(),
+            ..line.clone()
+        }));
+        lines
+    }
+
+    /// What this cron line alone does: its own description when it is one
+    /// of several lines, otherwise the explanation.
+    pub fn line_explanation(&self) -> &str {
+        self.line_explanation
+            .as_deref()
+            .unwrap_or(&self.explanation)
+    }
+
+    /// The same schedule meant as wall-clock time in `zone`.
+    pub fn with_time_zone(mut self, zone: Tz) -> Self {
+        self.explanation = format!("{} ({})", self.explanation, zone.name());
+        self.time_zone = Some(zone.name().to_string());
+        self
+    }
+
+    /// The zone the schedule is meant in, if it names a known one.
+    pub fn zone(&self) -> Option<Tz> {
+        self.time_zone.as_deref()?.parse().ok()
+    }
+
+    /// The schedule as a space-separated five-field cron expression, or its
+    /// macro such as `@daily`. Seconds and year are left out;
+    /// [`CronSpec::to_dialect`] checks that nothing is lost.
+    pub fn as_string(&self) -> String {
+        match &self.macro_name {
+            Some(name) => name.clone(),
+            None => self.five_fields(),
+        }
+    }
+
+    /// The five fields, even for a schedule written as a macro.
+    pub(crate) fn five_fields(&self) -> String {
+        format!(
+            "{} {} {} {} {}",
+            self.minute, self.hour, self.day_of_month, self.month, self.day_of_week
+        )
+    }
+
+    /// Every field the schedule has, seconds and year included, or its macro.
+    /// Several lines are separated by `; `.
+    pub(crate) fn full_string(&self) -> String {
+        if let Some(name) = &self.macro_name {
+            return name.clone();
+        }
+        if !self.extra_lines.is_empty() {
+            let lines: Vec<String> = self.lines().iter().map(Self::full_string).collect();
+            return lines.join("; ");
+        }
+        let mut parts: Vec<&str> = self.second.iter().map(String::as_str).collect();
+        parts.extend([
+            self.minute.as_str(),
+            self.hour.as_str(),
+            &self.day_of_month,
+            &self.month,
+            &self.day_of_week,
+        ]);
+        parts.extend(self.year.as_deref());
+        parts.join(" ")
+    }
+}
+
+/// A schedule together with the command it runs and its surrounding metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CronEntry {
+    /// Identifier written into the `# cronoisseur:id=` marker; derived from
+    /// the comment or command when not set (see [`CronEntry::managed_id`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub schedule: CronSpec,
+    pub command: String,
+    pub comment: Option<String>,
+    pub env: Vec<EnvVar>,
+}
+
+impl CronEntry {
+    /// The identifier that marks this entry's block in a crontab: `id` when
+    /// set, otherwise a slug of the comment, or of the command when there is
+    /// no comment.
+    pub fn managed_id(&self) -> String {
+        if let Some(id) = &self.id {
+            return id.clone();
+        }
+        slugify(self.comment.as_deref().unwrap_or(&self.command))
+    }
+}
+
+/// Lowercase `text`, turning every run of other characters into a single `-`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for ch in text.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 48 {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "entry".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parse_expression;
+
+    #[test]
+    fn lines_keep_their_own_description() {
+        let schedule = parse_expression("weekdays at 09:00 and 17:30").unwrap();
+        let lines = schedule.lines();
+        assert_eq!(
+            lines[0].line_explanation(),
+            "At 09:00 on Monday through Friday"
+        );
+        assert_eq!(
+            lines[1].line_explanation(),
+            "At 17:30 on Monday through Friday"
+        );
+        assert_eq!(lines[0].explanation, schedule.explanation);
+        let single = parse_expression("daily at 09:00").unwrap();
+        assert_eq!(single.line_explanation(), single.explanation);
+    }
+}
@@ -0,0 +1,492 @@
+use crate::crontab::{WriteStatus, merge_entry};
+use crate::error::{Error, Result};
+use crate::spec::CronEntry;
+use chrono::Utc;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Where backups of a crontab go before it is changed, and how many to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backups {
+    pub dir: PathBuf,
+    /// Backups kept per crontab; older ones are pruned. `0` disables backups.
+    pub keep: usize,
+}
+
+impl Backups {
+    /// `$XDG_STATE_HOME/cronoisseur/backups`, falling back to
+    /// `~/.local/state/cronoisseur/backups`.
+    pub fn default_dir() -> PathBuf {
+        let state = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME")
+                    .or_else(|| env::var_os("USERPROFILE"))
+                    .map(|home| PathBuf::from(home).join(".local").join("state"))
+            })
+            .unwrap_or_else(|| PathBuf::from("."));
+        state.join("cronoisseur").join("backups")
+    }
+}
+
+impl Default for Backups {
+    fn default() -> Self {
+        Self {
+            dir: Self::default_dir(),
+            keep: 10,
+        }
+    }
+}
+
+/// Suffix of a backup taken before the crontab existed; restoring it deletes
+/// the crontab again.
+const ABSENT_SUFFIX: &str = ".absent";
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// The contents of the crontab at `path`; empty when it does not exist yet.
+pub fn read_crontab(path: &Path) -> Result<String> {
+    Backend::File(path.to_path_buf()).read()
+}
+
+/// Replace the crontab at `path` with `contents`.
+///
+/// The new contents go to a temporary file next to `path` that is then
+/// renamed over it, so readers see either the old or the new crontab and
+/// never a partial one. Parent directories are created as needed.
+pub fn write_crontab(path: &Path, contents: &str) -> Result<()> {
+    write_atomic(path, contents)
+}
+
+/// Write generated output such as unit files or manifests to `path`,
+/// atomically, leaving a file that already matches alone.
+pub fn write_output(path: &Path, contents: &str) -> Result<WriteStatus> {
+    let status = match fs::read_to_string(path) {
+        Ok(existing) if existing == contents => return Ok(WriteStatus::Unchanged),
+        Ok(_) => WriteStatus::Replaced,
+        Err(err) if err.kind() == ErrorKind::NotFound => WriteStatus::New,
+        Err(err) => return Err(Error::io("Failed reading", path)(err)),
+    };
+    write_atomic(path, contents)?;
+    Ok(status)
+}
+
+/// Replace `path` with `contents` through a temporary file and a rename.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let parent = ensure_parent(path)?;
+    let temp = sibling(path, &format!("cronoisseur-{}.tmp", std::process::id()));
+    let result = (|| {
+        let mut file = File::create(&temp).map_err(Error::io("Failed creating", &temp))?;
+        file.write_all(contents.as_bytes())
+            .map_err(Error::io("Failed writing to", &temp))?;
+        file.sync_all()
+            .map_err(Error::io("Failed flushing", &temp))?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp, metadata.permissions())
+                .map_err(Error::io("Failed copying permissions to", &temp))?;
+        }
+        fs::rename(&temp, path).map_err(Error::io("Failed replacing", path))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    } else if let Some(parent) = parent
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+    result
+}
+
+/// Where a crontab lives and how it is read and replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// A cron file edited in place, e.g. `/etc/cron.d/backup`.
+    File(PathBuf),
+    /// A user's crontab, read with `crontab -l` and installed with
+    /// `crontab -`, so the cron daemon notices the change. `user` maps to
+    /// `crontab -u` and defaults to the invoking user.
+    Crontab { user: Option<String> },
+}
+
+impl Backend {
+    /// The current contents; empty when there is no crontab yet.
+    pub fn read(&self) -> Result<String> {
+        Ok(self.load()?.unwrap_or_default())
+    }
+
+    fn load(&self) -> Result<Option<String>> {
+        match self {
+            Backend::File(path) => match fs::read_to_string(path) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(Error::io("Failed reading", path)(err)),
+            },
+            Backend::Crontab { .. } => {
+                let output = self.crontab(&["-l"], None)?;
+                if output.status.success() {
+                    return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+                }
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if stderr.to_ascii_lowercase().contains("no crontab") {
+                    Ok(None)
+                } else {
+                    Err(self.command_error(&["-l"], &stderr))
+                }
+            }
+        }
+    }
+
+    /// Install `contents`, or delete the crontab when `None`.
+    fn store(&self, contents: Option<&str>) -> Result<()> {
+        match (self, contents) {
+            (Backend::File(path), Some(contents)) => write_crontab(path, contents),
+            (Backend::File(path), None) => match fs::remove_file(path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => {
+                    Err(Error::io("Failed removing", path)(err))
+                }
+                _ => Ok(()),
+            },
+            (Backend::Crontab { .. }, contents) => {
+                let args: &[&str] = if contents.is_some() { &["-"] } else { &["-r"] };
+                let output = self.crontab(args, contents)?;
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if output.status.success()
+                    || (contents.is_none() && stderr.to_ascii_lowercase().contains("no crontab"))
+                {
+                    Ok(())
+                } else {
+                    Err(self.command_error(args, &stderr))
+                }
+            }
+        }
+    }
+
+    /// Run the `crontab` binary with `args`, feeding it `input` on stdin.
+    fn crontab(&self, args: &[&str], input: Option<&str>) -> Result<Output> {
+        let mut command = Command::new("crontab");
+        if let Backend::Crontab { user: Some(user) } = self {
+            command.args(["-u", user]);
+        }
+        command
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let spawn_error = |err: io::Error| self.command_error(args, &err.to_string());
+        let mut child = command.spawn().map_err(spawn_error)?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input.as_bytes()).map_err(spawn_error)?;
+        }
+        child.wait_with_output().map_err(spawn_error)
+    }
+
+    fn command_error(&self, args: &[&str], message: &str) -> Error {
+        let mut command = vec!["crontab".to_string()];
+        if let Backend::Crontab { user: Some(user) } = self {
+            command.extend(["-u".to_string(), user.clone()]);
+        }
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        Error::Command {
+            command: command.join(" "),
+            message: message.trim().to_string(),
+        }
+    }
+
+    /// Backup names start with this, so backups of different crontabs can
+    /// share a directory: the file's absolute path flattened into one file
+    /// name, or `crontab-<user>`.
+    fn backup_prefix(&self) -> String {
+        let name = match self {
+            Backend::File(path) => std::path::absolute(path)
+                .unwrap_or_else(|_| path.clone())
+                .to_string_lossy()
+                .into_owned(),
+            Backend::Crontab { user } => format!("crontab-{}", user_name(user.as_deref())),
+        };
+        let flat: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.", flat.trim_start_matches('_'))
+    }
+
+    /// Take an exclusive advisory lock for this crontab, blocking until other
+    /// cronoisseur processes release it.
+    ///
+    /// For files the lock lives on a dot-prefixed sidecar rather than the
+    /// crontab itself, because the crontab is replaced by rename and a lock on
+    /// the old inode would not exclude anyone; cron skips dot-files in its
+    /// spool directories. `crontab` installs are locked in the backup
+    /// directory.
+    fn lock(&self, backups: &Backups) -> Result<File> {
+        let lock_path = match self {
+            Backend::File(path) => {
+                ensure_parent(path)?;
+                sibling(path, "lock")
+            }
+            Backend::Crontab { .. } => {
+                fs::create_dir_all(&backups.dir)
+                    .map_err(Error::io("Failed creating", &backups.dir))?;
+                backups.dir.join(format!(".{}lock", self.backup_prefix()))
+            }
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(Error::io("Failed opening lock file", &lock_path))?;
+        file.lock()
+            .map_err(Error::io("Failed locking", &lock_path))?;
+        Ok(file)
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::File(path) => write!(f, "{}", path.display()),
+            Backend::Crontab { user: Some(user) } => write!(f, "crontab -u {user}"),
+            Backend::Crontab { user: None } => write!(f, "crontab"),
+        }
+    }
+}
+
+fn user_name(user: Option<&str>) -> String {
+    user.map(str::to_string)
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .unwrap_or_else(|| "user".to_string())
+}
+
+/// Read, change and rewrite the crontab behind `backend` while holding its
+/// lock.
+///
+/// `edit` receives the current contents and returns the new contents (or
+/// `None` to leave the crontab alone) plus a value handed back to the caller.
+/// The crontab is backed up according to `backups` before it is replaced.
+pub fn edit_crontab<T, E: From<Error>>(
+    backend: &Backend,
+    backups: &Backups,
+    edit: impl FnOnce(&str) -> std::result::Result<(Option<String>, T), E>,
+) -> std::result::Result<T, E> {
+    let _lock = backend.lock(backups)?;
+    let existing = backend.load()?;
+    let (contents, value) = edit(existing.as_deref().unwrap_or_default())?;
+    if let Some(contents) = contents {
+        back_up(backend, existing.as_deref(), backups)?;
+        backend.store(Some(&contents))?;
+    }
+    Ok(value)
+}
+
+/// Write `entry` into the crontab behind `backend` unless it is already there.
+///
+/// See [`merge_entry`] for how an existing entry is recognised.
+pub fn write_entry(
+    backend: &Backend,
+    entry: &CronEntry,
+    allow_duplicate: bool,
+    backups: &Backups,
+) -> Result<WriteStatus> {
+    edit_crontab(backend, backups, |existing| {
+        let (contents, status) = merge_entry(existing, entry, allow_duplicate)?;
+        let changed = status != WriteStatus::Unchanged;
+        Ok((changed.then_some(contents), status))
+    })
+}
+
+/// Roll the crontab behind `backend` back to its most recent backup.
+///
+/// The backup is consumed, so calling this again steps further back. Returns
+/// the backup that was applied, or `None` when there is none.
+pub fn restore_backup(backend: &Backend, backups: &Backups) -> Result<Option<PathBuf>> {
+    let _lock = backend.lock(backups)?;
+    let Some(latest) = list_backups(backend, backups)?.pop() else {
+        return Ok(None);
+    };
+    if latest.to_string_lossy().ends_with(ABSENT_SUFFIX) {
+        backend.store(None)?;
+    } else {
+        let contents = fs::read_to_string(&latest).map_err(Error::io("Failed reading", &latest))?;
+        backend.store(Some(&contents))?;
+    }
+    fs::remove_file(&latest).map_err(Error::io("Failed removing", &latest))?;
+    Ok(Some(latest))
+}
+
+/// Backups of the crontab behind `backend`, oldest first.
+pub fn list_backups(backend: &Backend, backups: &Backups) -> Result<Vec<PathBuf>> {
+    let prefix = backend.backup_prefix();
+    let entries = match fs::read_dir(&backups.dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(Error::io("Failed reading", &backups.dir)(err)),
+    };
+    let mut found = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|candidate| {
+            candidate.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                let stamp = name
+                    .strip_prefix(&prefix)
+                    .and_then(|rest| {
+                        rest.strip_suffix(BACKUP_SUFFIX)
+                            .or_else(|| rest.strip_suffix(ABSENT_SUFFIX))
+                    })
+                    .unwrap_or_default();
+                is_stamp(stamp)
+            })
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    Ok(found)
+}
+
+fn back_up(backend: &Backend, existing: Option<&str>, backups: &Backups) -> Result<()> {
+    if backups.keep == 0 {
+        return Ok(());
+    }
+    fs::create_dir_all(&backups.dir).map_err(Error::io("Failed creating", &backups.dir))?;
+    let stamp = Utc::now().format("%Y%m%dT%H%M%S%.6fZ");
+    let suffix = if existing.is_some() {
+        BACKUP_SUFFIX
+    } else {
+        ABSENT_SUFFIX
+    };
+    let backup = backups
+        .dir
+        .join(format!("{}{stamp}{suffix}", backend.backup_prefix()));
+    fs::write(&backup, existing.unwrap_or_default())
+        .map_err(Error::io("Failed writing backup", &backup))?;
+
+    let found = list_backups(backend, backups)?;
+    for stale in &found[..found.len().saturating_sub(backups.keep)] {
+        fs::remove_file(stale).map_err(Error::io("Failed pruning backup", stale))?;
+    }
+    Ok(())
+}
+
+/// Whether `text` looks like the `%Y%m%dT%H%M%S%.6fZ` stamp in a backup name,
+/// so `/etc/cron.d/app` doesn't pick up the backups of `/etc/cron.d/app.old`.
+fn is_stamp(text: &str) -> bool {
+    text.len() == 23
+        && text.chars().enumerate().all(|(i, c)| match i {
+            8 => c == 'T',
+            15 => c == '.',
+            22 => c == 'Z',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+fn ensure_parent(path: &Path) -> Result<Option<&Path>> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        fs::create_dir_all(parent).map_err(Error::io("Failed creating", parent))?;
+    }
+    Ok(parent)
+}
+
+/// `.<file name>.<suffix>` in the same directory as `path`.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "crontab".to_string());
+    path.with_file_name(format!(".{name}.{suffix}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+
+    /// A fresh directory for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("cronoisseur-store-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(command: &str) -> CronEntry {
+        CronEntry {
+            id: None,
+            schedule: parse_expression("daily at 02:00").unwrap(),
+            command: command.to_string(),
+            comment: None,
+            env: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn every_change_is_backed_up_and_can_be_undone() {
+        let dir = scratch("undo");
+        let backend = Backend::File(dir.join("cron.d").join("app"));
+        let backups = Backups {
+            dir: dir.join("backups"),
+            keep: 10,
+        };
+        assert_eq!(
+            write_entry(&backend, &entry("a.sh"), false, &backups).unwrap(),
+            WriteStatus::New
+        );
+        write_entry(&backend, &entry("b.sh"), false, &backups).unwrap();
+        assert_eq!(
+            write_entry(&backend, &entry("b.sh"), false, &backups).unwrap(),
+            WriteStatus::Unchanged
+        );
+        assert_eq!(list_backups(&backend, &backups).unwrap().len(), 2);
+
+        restore_backup(&backend, &backups).unwrap().unwrap();
+        assert_eq!(
+            backend.read().unwrap(),
+            "# cronoisseur:id=a-sh\n0 2 * * * a.sh\n"
+        );
+        restore_backup(&backend, &backups).unwrap().unwrap();
+        assert!(!dir.join("cron.d").join("app").exists());
+        assert_eq!(restore_backup(&backend, &backups).unwrap(), None);
+    }
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let dir = scratch("prune");
+        let backend = Backend::File(dir.join("crontab"));
+        let backups = Backups {
+            dir: dir.join("backups"),
+            keep: 2,
+        };
+        for command in ["a.sh", "b.sh", "c.sh", "d.sh"] {
+            write_entry(&backend, &entry(command), false, &backups).unwrap();
+        }
+        let kept = list_backups(&backend, &backups).unwrap();
+        assert_eq!(kept.len(), 2);
+        let newest = fs::read_to_string(&kept[1]).unwrap();
+        assert!(newest.contains("c.sh") && !newest.contains("d.sh"));
+    }
+
+    #[test]
+    fn output_that_already_matches_is_left_alone() {
+        let path = scratch("output").join("unit.timer");
+        assert_eq!(write_output(&path, "a\n").unwrap(), WriteStatus::New);
+        assert_eq!(write_output(&path, "a\n").unwrap(), WriteStatus::Unchanged);
+        assert_eq!(write_output(&path, "b\n").unwrap(), WriteStatus::Replaced);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b\n");
+        assert!(is_stamp("20261017T120000.123456Z"));
+        assert!(!is_stamp("old"));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::crontab::{ID_MARKER, WriteStatus};
+use crate::dialect::STARTUP;
+use crate::error::{ConvertError, Result};
+use crate::field::{Base, Field, FieldKind, spec_extras, spec_fields};
+use crate::spec::{CronEntry, CronSpec};
+use crate::store::write_output;
+use serde::Serialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// `[Timer]` settings that have no cron equivalent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimerOptions {
+    /// Run a missed activation as soon as the machine is back up (`Persistent=`).
+    pub persistent: bool,
+    /// Spread activations over a random delay such as `5m` (`RandomizedDelaySec=`).
+    pub randomized_delay: Option<String>,
+}
+
+/// A `.timer` and `.service` pair that together replace one cron entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemdUnits {
+    /// Unit name without the `.timer` / `.service` suffix.
+    pub name: String,
+    pub on_calendar: Vec<String>,
+    pub timer: String,
+    pub service: String,
+}
+
+impl SystemdUnits {
+    pub fn timer_file(&self) -> String {
+        format!("{}.timer", self.name)
+    }
+
+    pub fn service_file(&self) -> String {
+        format!("{}.service", self.name)
+    }
+}
+
+/// Whether units are installed for the invoking user or system-wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitScope {
+    User,
+    System,
+}
+
+impl UnitScope {
+    /// `$XDG_CONFIG_HOME/systemd/user` (or `~/.config/systemd/user`) for user
+    /// units, `/etc/systemd/system` for system ones.
+    pub fn unit_dir(self) -> PathBuf {
+        match self {
+            UnitScope::System => PathBuf::from("/etc/systemd/system"),
+            UnitScope::User => env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+                .unwrap_or_else(|| PathBuf::from(".config"))
+                .join("systemd")
+                .join("user"),
+        }
+    }
+}
+
+/// Translate a schedule into `OnCalendar=` expressions.
+///
+/// Usually there is one. When both day-of-month and day-of-week are
+/// restricted cron fires on either, while systemd requires both, so each
+/// half gets its own expression, as does each line of a schedule with
+/// several. Seconds, years and the time zone carry over. Weekdays picked by
+/// their place in the month (`#` and `L`) become the days they can fall on,
+/// as in `Sun *-*-01..07`, and days counted back from the end of the month
+/// (`L`, `L-2`) use `~`, as in `*-*~01`; `W` days are an error.
+///
+/// ```
+/// use cronoisseur::{on_calendar, parse_expression};
+///
+/// let spec = parse_expression("weekdays at 07:15")?;
+/// assert_eq!(on_calendar(&spec)?, ["Mon..Fri *-*-* 07:15:00"]);
+/// # Ok::<(), cronoisseur::ConvertError>(())
+/// ```
+pub fn on_calendar(spec: &CronSpec) -> Result<Vec<String>, ConvertError> {
+    let mut expressions = Vec::new();
+    for line in spec.lines() {
+        expressions.extend(line_on_calendar(&line)?);
+    }
+    Ok(expressions)
+}
+
+/// The `OnCalendar=` expressions for one cron line.
+fn line_on_calendar(spec: &CronSpec) -> Result<Vec<String>, ConvertError> {
+    let fail = |reason: &str| ConvertError::Inexpressible {
+        cron: spec.full_string(),
+        target: "systemd".to_string(),
+        reason: reason.to_string(),
+    };
+    if spec.is_reboot() {
+        return Err(fail(STARTUP));
+    }
+    let [minute, hour, day_of_month, month, day_of_week] = spec_fields(spec)?;
+    let (second, year) = spec_extras(spec)?;
+    let places = ordinal_days(&day_of_week).filter(|_| day_of_month.is_star());
+    let month_ends = month_ends(&day_of_month).filter(|_| day_of_week.is_star());
+    if (day_of_month.has_special() && month_ends.is_none())
+        || (day_of_week.has_special() && places.is_none())
+    {
+        return Err(fail("it uses `L`, `W` or `#` days"));
+    }
+    let second = second.map_or_else(|| "00".to_string(), |second| component(&second));
+    let year = year.map_or_else(|| "*".to_string(), |year| component(&year));
+    let zone = spec
+        .time_zone
+        .as_ref()
+        .map_or_else(String::new, |zone| format!(" {zone}"));
+    let time = format!("{}:{}:{second}{zone}", component(&hour), component(&minute));
+    let date = |dom: &str| format!("{year}-{}-{dom}", component(&month));
+    if let Some(places) = places {
+        return Ok(places
+            .into_iter()
+            .map(|(day, days)| {
+                // `~` counts back from the end of the month in place of `-`.
+                let date = match days.strip_prefix('~') {
+                    Some(_) => format!("{year}-{}{days}", component(&month)),
+                    None => date(&days),
+                };
+                format!("{} {date} {time}", WEEKDAYS[day as usize])
+            })
+            .collect());
+    }
+    if let Some(ends) = month_ends {
+        return Ok(ends
+            .into_iter()
+            .map(|days| format!("{year}-{}{days} {time}", component(&month)))
+            .collect());
+    }
+    let weekdays = weekdays(&day_of_week);
+
+    let both = !day_of_month.is_star() && !day_of_week.is_star();
+    Ok(match weekdays {
+        Some(days) if both => vec![
+            format!("{} {time}", date(&component(&day_of_month))),
+            format!("{days} {} {time}", date("*")),
+        ],
+        Some(days) => vec![format!("{days} {} {time}", date(&component(&day_of_month)))],
+        None => vec![format!("{} {time}", date(&component(&day_of_month)))],
+    })
+}
+
+/// Build the timer and service units for `entry`, named after its managed id.
+///
+/// The service runs the command through `/bin/sh -c`, as cron would, with
+/// `--env` pairs as `Environment=` lines and the comment as `Description=`.
+pub fn render_systemd(
+    entry: &CronEntry,
+    options: &TimerOptions,
+) -> Result<SystemdUnits, ConvertError> {
+    let name = entry.managed_id();
+    let on_calendar = on_calendar(&entry.schedule)?;
+    let description = escape_specifiers(
+        entry
+            .comment
+            .as_deref()
+            .unwrap_or(&entry.schedule.explanation),
+    );
+    let marker = format!("{ID_MARKER}{name}");
+
+    let mut service = vec![
+        marker.clone(),
+        "[Unit]".to_string(),
+        format!("Description={description}"),
+        String::new(),
+        "[Service]".to_string(),
+        "Type=oneshot".to_string(),
+    ];
+    for env in &entry.env {
+        service.push(format!(
+            "Environment={}",
+            quote(&format!("{}={}", env.key, env.value), false)
+        ));
+    }
+    service.push(format!(
+        "ExecStart=/bin/sh -c {}",
+        quote(&entry.command, true)
+    ));
+
+    let mut timer = vec![
+        marker,
+        "[Unit]".to_string(),
+        format!("Description={description}"),
+        String::new(),
+        "[Timer]".to_string(),
+    ];
+    timer.extend(on_calendar.iter().map(|spec| format!("OnCalendar={spec}")));
+    if options.persistent {
+        timer.push("Persistent=true".to_string());
+    }
+    if let Some(delay) = &options.randomized_delay {
+        timer.push(format!("RandomizedDelaySec={delay}"));
+    }
+    timer.extend([
+        String::new(),
+        "[Install]".to_string(),
+        "WantedBy=timers.target".to_string(),
+    ]);
+
+    Ok(SystemdUnits {
+        name,
+        on_calendar,
+        timer: timer.join("\n") + "\n",
+        service: service.join("\n") + "\n",
+    })
+}
+
+/// Write both units into `dir`, leaving files that already match alone.
+///
+/// Systemd only notices the change after `systemctl daemon-reload`.
+pub fn write_systemd(dir: &Path, units: &SystemdUnits) -> Result<WriteStatus> {
+    let statuses = [
+        write_output(&dir.join(units.timer_file()), &units.timer)?,
+        write_output(&dir.join(units.service_file()), &units.service)?,
+    ];
+    Ok(match statuses {
+        [WriteStatus::Unchanged, WriteStatus::Unchanged] => WriteStatus::Unchanged,
+        [WriteStatus::New, WriteStatus::New] => WriteStatus::New,
+        _ => WriteStatus::Replaced,
+    })
+}
+
+/// A calendar component: `*`, values and `a..b` ranges, `start/step` for
+/// stepped wildcards. Stepped ranges are spelled out value by value.
+fn component(field: &Field) -> String {
+    let (min, _) = field.kind.bounds();
+    let pad = |value: u32| format!("{value:02}");
+    field
+        .items
+        .iter()
+        .map(|item| match (item.base, item.step) {
+            (Base::Any, None) => "*".to_string(),
+            (Base::Any, Some(step)) => format!("{}/{step}", pad(min)),
+            (Base::Value(value), None) => pad(value),
+            (Base::Value(value), Some(step)) => format!("{}/{step}", pad(value)),
+            (Base::Range(start, end), None) => format!("{}..{}", pad(start), pad(end)),
+            (Base::Range(start, end), Some(step)) => (start..=end)
+                .step_by(step as usize)
+                .map(pad)
+                .collect::<Vec<_>>()
+                .join(","),
+            _ => unreachable!("on_calendar rejects date-dependent days"),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// For a day-of-week made only of `#` and `L` items, each weekday with the
+/// days of the month it can fall on: `01..07` for the first, `~07/1` (the
+/// last seven days) for the last.
+fn ordinal_days(field: &Field) -> Option<Vec<(u32, String)>> {
+    let mut by_day: Vec<(u32, Vec<String>)> = Vec::new();
+    let mut lasts = Vec::new();
+    for item in &field.items {
+        match item.base {
+            Base::Nth(day, nth) => {
+                let range = format!("{:02}..{:02}", nth * 7 - 6, (nth * 7).min(31));
+                match by_day.iter_mut().find(|(known, _)| *known == day % 7) {
+                    Some((_, ranges)) => ranges.push(range),
+                    None => by_day.push((day % 7, vec![range])),
+                }
+            }
+            Base::LastOf(day) => lasts.push((day % 7, "~07/1".to_string())),
+            _ => return None,
+        }
+    }
+    let mut days: Vec<(u32, String)> = by_day
+        .into_iter()
+        .map(|(day, ranges)| (day, ranges.join(",")))
+        .collect();
+    days.extend(lasts);
+    Some(days)
+}
+
+/// The `~` day of each `L` or `L-n` item, counting the last day as `~01`;
+/// `None` unless every item is one.
+fn month_ends(field: &Field) -> Option<Vec<String>> {
+    field
+        .items
+        .iter()
+        .map(|item| match item.base {
+            Base::Last(offset) if item.step.is_none() => Some(format!("~{:02}", offset + 1)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The weekday prefix, or `None` when every day of the week is allowed.
+fn weekdays(field: &Field) -> Option<String> {
+    debug_assert_eq!(field.kind, FieldKind::DayOfWeek);
+    let set = field.set();
+    let days: Vec<u32> = set.values().collect();
+    if days.len() == 7 {
+        return None;
+    }
+    // Contiguous runs, with Sunday last so `Mon..Sun` style ranges come out.
+    let mut order: Vec<u32> = days.iter().copied().filter(|day| *day != 0).collect();
+    if set.contains(0) {
+        order.push(7);
+    }
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for day in order {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == day => *end = day,
+            _ => runs.push((day, day)),
+        }
+    }
+    let name = |day: u32| WEEKDAYS[day as usize % 7];
+    Some(
+        runs.into_iter()
+            .map(|(start, end)| match end - start {
+                0 => name(start).to_string(),
+                1 => format!("{},{}", name(start), name(end)),
+                _ => format!("{}..{}", name(start), name(end)),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+/// Double-quote a value for a unit file. `$` is doubled in command lines,
+/// where systemd would otherwise expand variables.
+fn quote(text: &str, command: bool) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '\\' | '"' => {
+                quoted.push('\\');
+                quoted.push(ch);
+            }
+            '%' => quoted.push_str("%%"),
+            '$' if command => quoted.push_str("$$"),
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn escape_specifiers(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::parse::{parse_expression, parse_expression_in};
+    use crate::spec::EnvVar;
+
+    fn calendar(expression: &str) -> Vec<String> {
+        on_calendar(&parse_expression(expression).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn either_day_field_gets_its_own_expression() {
+        assert_eq!(
+            calendar("0 0 13 * 5"),
+            ["*-*-13 00:00:00", "Fri *-*-* 00:00:00"]
+        );
+        assert_eq!(
+            calendar("daily at 09:00 and 17:30"),
+            ["*-*-* 09:00:00", "*-*-* 17:30:00"]
+        );
+    }
+
+    #[test]
+    fn quartz_days_map_to_day_ranges() {
+        assert_eq!(
+            calendar("first monday of the month at 09:00"),
+            ["Mon *-*-01..07 09:00:00"]
+        );
+        assert_eq!(
+            calendar("last friday of the month at 09:00"),
+            ["Fri *-*~07/1 09:00:00"]
+        );
+        assert_eq!(
+            calendar("2 days before the end of the month at 06:00"),
+            ["*-*~03 06:00:00"]
+        );
+        let nearest = parse_expression_in("0 0 9 15W * ?", Dialect::Quartz).unwrap();
+        assert!(on_calendar(&nearest).is_err());
+    }
+
+    #[test]
+    fn steps_and_zones_carry_over() {
+        assert_eq!(calendar("every 15 minutes"), ["*-*-* *:00/15:00"]);
+        assert_eq!(calendar("5-50/15 * * * *"), ["*-*-* *:05,20,35,50:00"]);
+        assert_eq!(
+            calendar("daily at 09:00 Europe/Berlin"),
+            ["*-*-* 09:00:00 Europe/Berlin"]
+        );
+    }
+
+    #[test]
+    fn units_run_the_command_through_a_shell() {
+        let entry = CronEntry {
+            id: None,
+            schedule: parse_expression("daily at 02:00").unwrap(),
+            command: "echo $HOME > /tmp/home".to_string(),
+            comment: Some("100% backed up".to_string()),
+            env: vec![EnvVar {
+                key: "MODE".to_string(),
+                value: "full run".to_string(),
+            }],
+        };
+        let units = render_systemd(
+            &entry,
+            &TimerOptions {
+                persistent: true,
+                randomized_delay: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(units.name, "100-backed-up");
+        assert_eq!(
+            units.service,
+            "# cronoisseur:id=100-backed-up\n[Unit]\nDescription=100%% backed up\n\n[Service]\nType=oneshot\nEnvironment=\"MODE=full run\"\nExecStart=/bin/sh -c \"echo $$HOME > /tmp/home\"\n"
+        );
+        assert!(
+            units
+                .timer
+                .contains("OnCalendar=*-*-* 02:00:00\nPersistent=true\n")
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Render `value` as block-style YAML.
+///
+/// Strings are always double-quoted (JSON strings are valid YAML), so values
+/// like `yes`, `on` or `0755` survive YAML 1.1 parsers such as the one behind
+/// `kubectl` without turning into booleans or numbers.
+pub(crate) fn to_yaml(value: &impl Serialize) -> String {
+    let value = serde_json::to_value(value).expect("plain data always serializes");
+    let mut out = String::new();
+    emit(&value, 0, &mut out);
+    out
+}
+
+fn emit(value: &Value, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                out.push_str(&format!("{pad}{}:", key_text(key)));
+                nested(value, indent + 2, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                let mut block = String::new();
+                match item {
+                    Value::Object(map) if !map.is_empty() => emit(item, indent + 2, &mut block),
+                    _ => {
+                        block.push_str(&" ".repeat(indent + 2));
+                        nested_inline(item, indent + 2, &mut block);
+                    }
+                }
+                out.push_str(&format!("{pad}- {}", &block[indent + 2..]));
+            }
+        }
+        scalar => {
+            out.push_str(&format!("{pad}{scalar}\n"));
+        }
+    }
+}
+
+/// The rest of a `key:` line: a scalar on the same line, or a nested block.
+fn nested(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            emit(value, indent, out);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            emit(value, indent - 2, out);
+        }
+        _ => {
+            out.push(' ');
+            nested_inline(value, indent, out);
+        }
+    }
+}
+
+fn nested_inline(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::Object(map) if map.is_empty() => out.push_str("{}\n"),
+        Value::Array(items) if items.is_empty() => out.push_str("[]\n"),
+        Value::Object(_) | Value::Array(_) => {
+            out.push('\n');
+            emit(value, indent, out);
+        }
+        scalar => {
+            out.push_str(&scalar.to_string());
+            out.push('\n');
+        }
+    }
+}
+
+/// Keys stay bare when they are plain identifiers such as `apiVersion` or
+/// `app.kubernetes.io/managed-by`.
+fn key_text(key: &str) -> String {
+    let plain = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c));
+    if plain {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nests_blocks_and_quotes_strings() {
+        let value = json!({
+            "on": "yes",
+            "$CI": 1,
+            "jobs": [{ "name": "a", "args": ["x", "0755"] }, "plain"],
+            "empty": {},
+            "none": [],
+        });
+        assert_eq!(
+            to_yaml(&value),
+            "on: \"yes\"\n\"$CI\": 1\njobs:\n- name: \"a\"\n  args:\n  - \"x\"\n  - \"0755\"\n- \"plain\"\nempty: {}\nnone: []\n"
+        );
+    }
+}
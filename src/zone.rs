@@ -0,0 +1,425 @@
+use crate::describe::explain;
+use crate::dialect::vixie_fields;
+use crate::error::ConvertError;
+use crate::field::{Field, FieldKind, compress};
+use crate::spec::{CronEntry, CronSpec, EnvVar};
+use chrono::{DateTime, Datelike, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, BTreeSet};
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// A schedule moved into another zone, possibly split over several lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZoneShift {
+    pub schedules: Vec<CronSpec>,
+    pub warnings: Vec<String>,
+}
+
+/// How a crontab entry carries the zone its schedule is meant in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZoneStyle {
+    /// A `CRON_TZ=` line, which cronie evaluates the schedule in.
+    #[default]
+    CronTz,
+    /// A `TZ=` line, for crons that take the schedule's zone from `TZ`.
+    Tz,
+    /// No zone at all: the fields are rewritten into the host's zone.
+    Shift,
+}
+
+/// An entry ready for a crontab, plus anything worth knowing about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedEntry {
+    pub entry: CronEntry,
+    pub warnings: Vec<String>,
+}
+
+/// The zone this machine's clock is set to, or UTC when it can't be told.
+pub fn host_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Prepare an entry whose schedule has a [`CronSpec::time_zone`] for a
+/// crontab on a machine running in `host`.
+///
+/// [`ZoneStyle::CronTz`] keeps the zone, which
+/// [`render_entry`](crate::render_entry) writes as `CRON_TZ=`.
+/// [`ZoneStyle::Tz`] turns it into a `TZ=` variable instead.
+/// [`ZoneStyle::Shift`] moves the fields into `host` with [`shift_zone`],
+/// possibly over several lines. Entries without a zone are returned as they
+/// are.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use cronoisseur::{CronEntry, ZoneStyle, localize_entry, parse_expression};
+///
+/// let entry = CronEntry {
+///     id: None,
+///     schedule: parse_expression("weekdays at 00:30 Europe/Berlin")?,
+///     command: "sync.sh".to_string(),
+///     comment: None,
+///     env: Vec::new(),
+/// };
+/// let at = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+/// let local = localize_entry(&entry, ZoneStyle::Shift, chrono_tz::UTC, at)?;
+/// assert_eq!(local.entry.schedule.as_string(), "30 23 * * 0-4");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn localize_entry(
+    entry: &CronEntry,
+    style: ZoneStyle,
+    host: Tz,
+    at: DateTime<Utc>,
+) -> Result<LocalizedEntry, ConvertError> {
+    let mut entry = entry.clone();
+    let mut warnings = Vec::new();
+    let Some(zone) = entry.schedule.zone() else {
+        return Ok(LocalizedEntry { entry, warnings });
+    };
+    match style {
+        ZoneStyle::CronTz => {}
+        ZoneStyle::Tz => {
+            entry.schedule.time_zone = None;
+            entry.env.retain(|env| env.key != "TZ");
+            entry.env.push(EnvVar {
+                key: "TZ".to_string(),
+                value: zone.name().to_string(),
+            });
+        }
+        ZoneStyle::Shift if zone == host => entry.schedule.time_zone = None,
+        ZoneStyle::Shift => {
+            let shift = shift_zone(&entry.schedule, zone, host, at)?;
+            let explanation = shift
+                .schedules
+                .iter()
+                .map(|line| line.explanation.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+            entry.schedule = CronSpec::from_lines(shift.schedules, explanation);
+            warnings = shift.warnings;
+        }
+    }
+    Ok(LocalizedEntry { entry, warnings })
+}
+
+/// Rewrite a schedule meant as wall-clock time in `zone` so it fires at the
+/// same moments when evaluated in UTC.
+///
+/// The offset in effect at `at` is used. Times that cross midnight move to
+/// the neighbouring weekday or day of the month; hours that land on
+/// different days get separate lines. Zones with daylight saving time get a
+/// warning, since a fixed UTC schedule drifts by an hour for part of the year.
+/// See [`shift_zone`] to move into a zone other than UTC.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use cronoisseur::{parse_expression, shift_to_utc};
+///
+/// let spec = parse_expression("weekdays at 00:30")?;
+/// let at = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+/// let shift = shift_to_utc(&spec, chrono_tz::Europe::Berlin, at)?;
+/// assert_eq!(shift.schedules[0].as_string(), "30 23 * * 0-4");
+/// # Ok::<(), cronoisseur::ConvertError>(())
+/// ```
+pub fn shift_to_utc(
+    spec: &CronSpec,
+    zone: Tz,
+    at: DateTime<Utc>,
+) -> Result<ZoneShift, ConvertError> {
+    shift_zone(spec, zone, Tz::UTC, at)
+}
+
+/// Rewrite a schedule meant as wall-clock time in `from` so it fires at the
+/// same moments when evaluated in `to`, as [`shift_to_utc`] does for UTC.
+///
+/// The zones' difference at `at` is used; when it changes during the year,
+/// because only one of them observes daylight saving time or they switch on
+/// different dates, there is a warning.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use cronoisseur::{parse_expression, shift_zone};
+///
+/// let spec = parse_expression("weekdays at 09:00")?;
+/// let at = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+/// let shift = shift_zone(&spec, chrono_tz::Asia::Tokyo, chrono_tz::America::New_York, at)?;
+/// assert_eq!(shift.schedules[0].as_string(), "0 19 * * 0-4");
+/// # Ok::<(), cronoisseur::ConvertError>(())
+/// ```
+pub fn shift_zone(
+    spec: &CronSpec,
+    from: Tz,
+    to: Tz,
+    at: DateTime<Utc>,
+) -> Result<ZoneShift, ConvertError> {
+    if !spec.extra_lines.is_empty() {
+        let mut schedules = Vec::new();
+        let mut warnings: Vec<String> = Vec::new();
+        for line in spec.lines() {
+            let shift = shift_zone(&line, from, to, at)?;
+            schedules.extend(shift.schedules);
+            for warning in shift.warnings {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        return Ok(ZoneShift {
+            schedules,
+            warnings,
+        });
+    }
+    let fields = vixie_fields(spec, to.name())?;
+    let [minute, hour, day_of_month, month, day_of_week] = &fields;
+    let offset = offset_minutes(from, at) - offset_minutes(to, at);
+
+    let mut warnings = Vec::new();
+    let year = at.year();
+    let difference = |month| {
+        let noon = Utc.with_ymd_and_hms(year, month, 1, 12, 0, 0).unwrap();
+        offset_minutes(from, noon) - offset_minutes(to, noon)
+    };
+    if difference(1) != difference(7) {
+        let date = at.with_timezone(&from).format("%Y-%m-%d");
+        warnings.push(if to == Tz::UTC {
+            format!(
+                "{from} observes daylight saving time; converted with its offset on {date} (UTC{}), so runs are an hour off for part of the year",
+                format_offset(offset)
+            )
+        } else {
+            format!(
+                "{from} and {to} are not always the same distance apart; converted with the difference on {date} ({}), so runs are an hour off for part of the year",
+                format_offset(offset)
+            )
+        });
+    }
+
+    let days_free = day_of_month.is_full() && month.is_full() && day_of_week.is_full();
+    let mut by_day: BTreeMap<i32, BTreeMap<u32, BTreeSet<u32>>> = BTreeMap::new();
+    for h in hour.set().values() {
+        for m in minute.set().values() {
+            let utc = (h * 60 + m) as i32 - offset;
+            let delta = if days_free {
+                0
+            } else {
+                utc.div_euclid(MINUTES_PER_DAY)
+            };
+            let utc = utc.rem_euclid(MINUTES_PER_DAY) as u32;
+            by_day
+                .entry(delta)
+                .or_default()
+                .entry(utc / 60)
+                .or_default()
+                .insert(utc % 60);
+        }
+    }
+
+    let mut schedules = Vec::new();
+    for (delta, hours) in by_day {
+        let [dom, mon, dow] = shift_days(spec, &fields, delta, to)?;
+        let mut by_minutes: BTreeMap<Vec<u32>, Vec<u32>> = BTreeMap::new();
+        for (h, minutes) in hours {
+            by_minutes
+                .entry(minutes.into_iter().collect())
+                .or_default()
+                .push(h);
+        }
+        for (minutes, hours) in by_minutes {
+            let line = [
+                compress(FieldKind::Minute, &minutes),
+                compress(FieldKind::Hour, &hours),
+                dom.clone(),
+                mon.clone(),
+                dow.clone(),
+            ];
+            let explanation = format!("{} ({})", explain(&line.join(" "))?, to.name());
+            let [minute, hour, day_of_month, month, day_of_week] = line;
+            schedules.push(CronSpec::new(
+                minute,
+                hour,
+                day_of_month,
+                month,
+                day_of_week,
+                explanation,
+            ));
+        }
+    }
+    Ok(ZoneShift {
+        schedules,
+        warnings,
+    })
+}
+
+/// The day-of-month, month and day-of-week fields moved `delta` days.
+fn shift_days(
+    spec: &CronSpec,
+    fields: &[Field; 5],
+    delta: i32,
+    to: Tz,
+) -> Result<[String; 3], ConvertError> {
+    let [_, _, day_of_month, month, day_of_week] = fields;
+    if delta == 0 {
+        return Ok([
+            day_of_month.canonical(),
+            month.canonical(),
+            day_of_week.canonical(),
+        ]);
+    }
+    let fail = |reason: &str| ConvertError::Inexpressible {
+        cron: spec.as_string(),
+        target: to.name().to_string(),
+        reason: reason.to_string(),
+    };
+    let direction = if delta < 0 { "previous" } else { "next" };
+
+    let dom_restricted = !day_of_month.is_full();
+    let dow_restricted = !day_of_week.is_full();
+    if dom_restricted && dow_restricted && day_of_month.is_star() != day_of_week.is_star() {
+        return Err(fail(
+            "a stepped day of the month combined with weekdays can't be moved to another day",
+        ));
+    }
+    let dom = if dom_restricted {
+        let days: Vec<u32> = day_of_month.set().values().collect();
+        let crosses = days
+            .iter()
+            .any(|day| (delta < 0 && *day == 1) || (delta > 0 && *day >= 28));
+        if crosses {
+            return Err(fail(&format!(
+                "some runs would move into the {direction} month in {to}"
+            )));
+        }
+        let shifted: Vec<u32> = days
+            .iter()
+            .map(|day| day.saturating_add_signed(delta))
+            .collect();
+        compress(FieldKind::DayOfMonth, &shifted)
+    } else if !month.is_full() {
+        return Err(fail(&format!(
+            "runs at the edge of each month would move into the {direction} month in {to}"
+        )));
+    } else {
+        day_of_month.canonical()
+    };
+    let dow = if dow_restricted {
+        let shifted: Vec<u32> = day_of_week
+            .set()
+            .values()
+            .map(|day| (day as i32 + delta).rem_euclid(7) as u32)
+            .collect();
+        compress(FieldKind::DayOfWeek, &shifted)
+    } else {
+        day_of_week.canonical()
+    };
+    Ok([dom, month.canonical(), dow])
+}
+
+/// Minutes east of UTC in `zone` at `at`.
+pub(crate) fn offset_minutes(zone: Tz, at: DateTime<Utc>) -> i32 {
+    zone.offset_from_utc_datetime(&at.naive_utc())
+        .fix()
+        .local_minus_utc()
+        / 60
+}
+
+/// Whether `zone` keeps UTC's clock all year.
+pub(crate) fn is_utc(zone: Tz) -> bool {
+    [1, 7].into_iter().all(|month| {
+        offset_minutes(
+            zone,
+            Utc.with_ymd_and_hms(2000, month, 1, 12, 0, 0).unwrap(),
+        ) == 0
+    })
+}
+
+/// `+01:00`, `-05:00`, `+05:30`.
+pub(crate) fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_expression;
+    use chrono_tz::Europe::Berlin;
+
+    fn winter() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap()
+    }
+
+    fn entry(expression: &str) -> CronEntry {
+        CronEntry {
+            id: None,
+            schedule: parse_expression(expression).unwrap(),
+            command: "sync.sh".to_string(),
+            comment: None,
+            env: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hours_that_cross_midnight_get_a_line_of_their_own() {
+        let spec = parse_expression("weekdays at 00:30 and 12:00").unwrap();
+        let shift = shift_to_utc(&spec, Berlin, winter()).unwrap();
+        let lines: Vec<String> = shift.schedules.iter().map(CronSpec::as_string).collect();
+        assert_eq!(lines, ["30 23 * * 0-4", "0 11 * * 1-5"]);
+        assert_eq!(
+            shift.warnings,
+            [
+                "Europe/Berlin observes daylight saving time; converted with its offset on 2026-01-15 (UTC+01:00), so runs are an hour off for part of the year"
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_that_would_change_month_are_an_error() {
+        let spec = parse_expression("0 0 1 * *").unwrap();
+        let err = shift_to_utc(&spec, Berlin, winter()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("some runs would move into the previous month in UTC"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn each_style_carries_the_zone_its_own_way() {
+        let zoned = entry("daily at 05:00 Europe/Berlin");
+
+        let kept = localize_entry(&zoned, ZoneStyle::CronTz, Tz::UTC, winter()).unwrap();
+        assert_eq!(kept.entry, zoned);
+
+        let tz = localize_entry(&zoned, ZoneStyle::Tz, Tz::UTC, winter()).unwrap();
+        assert_eq!(tz.entry.schedule.time_zone, None);
+        assert_eq!(
+            tz.entry.env,
+            [EnvVar {
+                key: "TZ".to_string(),
+                value: "Europe/Berlin".to_string(),
+            }]
+        );
+
+        let shifted = localize_entry(&zoned, ZoneStyle::Shift, Tz::UTC, winter()).unwrap();
+        assert_eq!(shifted.entry.schedule.as_string(), "0 4 * * *");
+        assert_eq!(shifted.entry.schedule.time_zone, None);
+
+        let home = localize_entry(&zoned, ZoneStyle::Shift, Berlin, winter()).unwrap();
+        assert_eq!(home.entry.schedule.as_string(), "0 5 * * *");
+        assert!(home.warnings.is_empty());
+    }
+
+    #[test]
+    fn offsets_are_formatted_with_sign_and_minutes() {
+        assert_eq!(format_offset(60), "+01:00");
+        assert_eq!(format_offset(-300), "-05:00");
+        assert_eq!(format_offset(330), "+05:30");
+        assert!(is_utc(chrono_tz::Etc::UTC));
+        assert!(!is_utc(chrono_tz::Europe::London));
+    }
+}
@@ -0,0 +1,215 @@
+//! `Backend::Crontab` against a stub `crontab` script put first on PATH.
+//!
+//! The stub keeps each user's state in files next to itself, named after the
+//! `-u` user (`default` without one): `<user>.args` logs every invocation,
+//! `<user>.crontab` is the installed crontab, `<user>.stdin` the last input
+//! to `crontab -`, and `<user>.fail` makes installs fail.
+#![cfg(unix)]
+
+use cronoisseur::{
+    Backend, Backups, CronEntry, EnvVar, Error, WriteStatus, host_zone, parse_crontab,
+    parse_expression, restore_backup, write_entry,
+};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const STUB: &str = r#"#!/bin/sh
+dir=$(dirname "$0")
+user=default
+if [ "$1" = "-u" ]; then user=$2; fi
+echo "$*" >> "$dir/$user.args"
+for last; do :; done
+case "$last" in
+  -l)
+    if [ -f "$dir/$user.crontab" ]; then cat "$dir/$user.crontab"
+    else echo "no crontab for $user" >&2; exit 1; fi ;;
+  -)
+    if [ -f "$dir/$user.fail" ]; then
+      cat > /dev/null; echo "crontab: installing new crontab failed" >&2; exit 1
+    fi
+    cat > "$dir/$user.stdin"; cp "$dir/$user.stdin" "$dir/$user.crontab" ;;
+  -r) rm -f "$dir/$user.crontab" ;;
+esac
+"#;
+
+/// The directory holding the stub, installed and put on PATH once.
+fn stub_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = env::temp_dir().join(format!("cronoisseur-crontab-stub-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let stub = dir.join("crontab");
+        fs::write(&stub, STUB).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = env::join_paths(
+            [dir.clone()]
+                .into_iter()
+                .chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+        )
+        .unwrap();
+        // SAFETY: every test waits here before touching the environment or
+        // spawning anything, so nothing reads PATH while it changes.
+        unsafe { env::set_var("PATH", path) };
+        dir
+    })
+}
+
+fn state(user: &str, suffix: &str) -> PathBuf {
+    stub_dir().join(format!("{user}.{suffix}"))
+}
+
+fn invocations(user: &str) -> Vec<String> {
+    fs::read_to_string(state(user, "args"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn backups(user: &str) -> Backups {
+    Backups {
+        dir: stub_dir().join(format!("backups-{user}")),
+        keep: 10,
+    }
+}
+
+fn entry(command: &str) -> CronEntry {
+    CronEntry {
+        id: None,
+        schedule: parse_expression("daily at 02:00").unwrap(),
+        command: command.to_string(),
+        comment: None,
+        env: Vec::new(),
+    }
+}
+
+#[test]
+fn missing_crontab_reads_as_empty() {
+    stub_dir();
+    let backend = Backend::Crontab {
+        user: Some("fresh".to_string()),
+    };
+    assert_eq!(backend.read().unwrap(), "");
+    assert_eq!(invocations("fresh"), ["-u fresh -l"]);
+}
+
+#[test]
+fn without_a_user_no_u_flag_is_passed() {
+    stub_dir();
+    fs::write(state("default", "crontab"), "0 1 * * * true\n").unwrap();
+    let backend = Backend::Crontab { user: None };
+    assert_eq!(backend.read().unwrap(), "0 1 * * * true\n");
+    assert_eq!(invocations("default"), ["-l"]);
+}
+
+#[test]
+fn write_pipes_the_merged_crontab_to_crontab_dash() {
+    stub_dir();
+    fs::write(state("alice", "crontab"), "MAILTO=ops\n").unwrap();
+    let backend = Backend::Crontab {
+        user: Some("alice".to_string()),
+    };
+    let status = write_entry(&backend, &entry("backup.sh"), false, &backups("alice")).unwrap();
+    assert_eq!(status, WriteStatus::New);
+    assert_eq!(invocations("alice"), ["-u alice -l", "-u alice -"]);
+    assert_eq!(
+        fs::read_to_string(state("alice", "stdin")).unwrap(),
+        "MAILTO=ops\n# cronoisseur:id=backup-sh\n0 2 * * * backup.sh\n"
+    );
+
+    // Writing the same entry again reads the crontab but installs nothing.
+    let status = write_entry(&backend, &entry("backup.sh"), false, &backups("alice")).unwrap();
+    assert_eq!(status, WriteStatus::Unchanged);
+    assert_eq!(invocations("alice").last().unwrap(), "-u alice -l");
+}
+
+#[test]
+fn failed_install_is_an_error() {
+    stub_dir();
+    fs::write(state("bob", "fail"), "").unwrap();
+    let backend = Backend::Crontab {
+        user: Some("bob".to_string()),
+    };
+    let err = write_entry(&backend, &entry("sync.sh"), false, &backups("bob")).unwrap_err();
+    match err {
+        Error::Command { command, message } => {
+            assert_eq!(command, "crontab -u bob -");
+            assert_eq!(message, "crontab: installing new crontab failed");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+    assert!(!state("bob", "crontab").exists());
+}
+
+#[test]
+fn restoring_a_crontab_that_did_not_exist_removes_it() {
+    stub_dir();
+    let backend = Backend::Crontab {
+        user: Some("carol".to_string()),
+    };
+    write_entry(&backend, &entry("report.sh"), false, &backups("carol")).unwrap();
+    assert!(state("carol", "crontab").exists());
+
+    let restored = restore_backup(&backend, &backups("carol")).unwrap();
+    assert!(restored.is_some());
+    assert_eq!(invocations("carol").last().unwrap(), "-u carol -r");
+    assert!(!state("carol", "crontab").exists());
+}
+
+#[test]
+fn a_zoned_entry_does_not_leak_its_zone_into_the_next() {
+    stub_dir();
+    let backend = Backend::Crontab {
+        user: Some("dave".to_string()),
+    };
+    let zoned = CronEntry {
+        schedule: parse_expression("daily at 05:00 Europe/Berlin").unwrap(),
+        ..entry("a.sh")
+    };
+    write_entry(&backend, &zoned, false, &backups("dave")).unwrap();
+    write_entry(&backend, &entry("b.sh"), false, &backups("dave")).unwrap();
+
+    let crontab = parse_crontab(&backend.read().unwrap(), false);
+    let [first, second] = &crontab.jobs[..] else {
+        panic!("expected two jobs, found {:?}", crontab.jobs);
+    };
+    assert_eq!(first.entry.schedule.zone(), Some(chrono_tz::Europe::Berlin));
+    assert_eq!(
+        second.entry.schedule.zone().unwrap_or(host_zone()),
+        host_zone()
+    );
+}
+
+#[test]
+fn a_tz_variable_is_put_back_after_its_block() {
+    stub_dir();
+    let backend = Backend::Crontab {
+        user: Some("erin".to_string()),
+    };
+    fs::write(state("erin", "crontab"), "TZ=America/New_York\n").unwrap();
+    let zoned = CronEntry {
+        env: vec![EnvVar {
+            key: "TZ".to_string(),
+            value: "Europe/Berlin".to_string(),
+        }],
+        ..entry("a.sh")
+    };
+    write_entry(&backend, &zoned, false, &backups("erin")).unwrap();
+    write_entry(&backend, &entry("b.sh"), false, &backups("erin")).unwrap();
+
+    let crontab = parse_crontab(&backend.read().unwrap(), false);
+    let tz = |job: usize| {
+        crontab.jobs[job]
+            .entry
+            .env
+            .iter()
+            .find(|env| env.key == "TZ")
+            .map(|env| env.value.clone())
+    };
+    assert_eq!(tz(0).as_deref(), Some("Europe/Berlin"));
+    assert_eq!(tz(1).as_deref(), Some("America/New_York"));
+}